@@ -55,7 +55,11 @@ pub enum IoError {
 impl IoError {
     /// Whether this error means that a timeout occured when querying a node.
     pub fn is_timeout(&self) -> bool {
-        matches!(self, Self::Timeout(_))
+        match self {
+            Self::Timeout(_) => true,
+            Self::IoError(e) => e.is_timeout(),
+            _ => false,
+        }
     }
 }
 
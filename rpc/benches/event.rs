@@ -0,0 +1,60 @@
+//! Benchmarks for deserializing websocket event frames
+
+use async_tungstenite::tungstenite::Message;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use tendermint::abci::Transaction;
+use tendermint_rpc::event_listener::{ResultEvent, WrappedResultEvent};
+use tendermint_rpc::test_support::read_json_fixture;
+use tendermint_rpc::Response;
+
+const FIXTURES: &str = "./tests/support";
+
+fn deserialize_event(c: &mut Criterion) {
+    for fixture in &["event_new_block", "event_tx_v0_34"] {
+        let frame = read_json_fixture(FIXTURES, fixture);
+
+        c.bench_function(&format!("{} (direct)", fixture), |b| {
+            b.iter(|| ResultEvent::from_string(&frame).unwrap())
+        });
+
+        // How frames were parsed before: the websocket message was formatted
+        // back into a new string, which was then parsed
+        let message = Message::text(frame.clone());
+        c.bench_function(&format!("{} (via Message::to_string)", fixture), |b| {
+            b.iter(|| {
+                serde_json::from_str::<WrappedResultEvent>(&message.to_string())
+                    .unwrap()
+                    .into_result()
+                    .unwrap()
+            })
+        });
+    }
+}
+
+/// Build a `NewBlock` event frame for a block of `count` transactions of
+/// `size` bytes each
+fn large_new_block_frame(count: usize, size: usize) -> Vec<u8> {
+    let mut frame: serde_json::Value =
+        serde_json::from_str(&read_json_fixture(FIXTURES, "event_new_block")).unwrap();
+    let tx = serde_json::to_value(Transaction::new(vec![0x42; size])).unwrap();
+    frame["result"]["data"]["value"]["block"]["data"]["txs"] =
+        serde_json::Value::Array(vec![tx; count]);
+    serde_json::to_vec(&frame).unwrap()
+}
+
+fn deserialize_large_event(c: &mut Criterion) {
+    // Roughly 20MB, i.e. the time a large block holds up the thread parsing
+    // it, which `EventListener::with_large_frame_size` moves off the runtime
+    let frame = large_new_block_frame(1_000, 15_000);
+
+    let mut group = c.benchmark_group("large_event");
+    group.sample_size(10);
+    group.bench_function("event_new_block (20MB)", |b| {
+        b.iter(|| ResultEvent::from_string(&frame).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, deserialize_event, deserialize_large_event);
+criterion_main!(benches);
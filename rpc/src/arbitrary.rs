@@ -0,0 +1,419 @@
+//! `proptest` strategies generating RPC responses, for property-based tests
+//!
+//! The generated responses are structurally valid: blocks, commits and
+//! validators are built with `tendermint-testgen`, so their hashes and
+//! signatures check out. Edge cases such as height 1, empty transaction lists
+//! and absent commit signatures are generated more often than by chance.
+//! Fields which can only be built by deserializing them, such as those of
+//! node information, are generated as JSON and parsed.
+
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+use serde_json::json;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use tendermint::abci::tag::Tag;
+use tendermint::abci::{self, transaction, Code, Data, DeliverTx, Log, Transaction};
+use tendermint::{block, evidence, node, validator, Block, Hash, Time};
+use tendermint_testgen::{self as testgen, Generator};
+
+use crate::endpoint::{
+    block as block_endpoint, block_results, broadcast, net_info, status, validators,
+};
+
+/// Block heights, starting at 1
+pub fn height() -> impl Strategy<Value = block::Height> {
+    prop_oneof![Just(1u64), 1..=i64::MAX as u64].prop_map(block::Height::from)
+}
+
+/// ABCI response codes, successful ones included
+pub fn code() -> impl Strategy<Value = Code> {
+    prop_oneof![Just(0u32), any::<u32>()].prop_map(Code::from)
+}
+
+/// ABCI response data, possibly empty
+pub fn data() -> impl Strategy<Value = Data> {
+    vec(any::<u8>(), 0..64).prop_map(|bytes| {
+        let hex: String = bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+        hex.parse().unwrap()
+    })
+}
+
+/// ABCI logs of printable characters
+pub fn log() -> impl Strategy<Value = Log> {
+    "[ -~]{0,32}".prop_map(|log| Log::from(log.as_str()))
+}
+
+/// Times between the Unix epoch and the year 2100, with nanoseconds
+pub fn time() -> impl Strategy<Value = Time> {
+    (0..4_102_444_800u64, 0..1_000_000_000u32)
+        .prop_map(|(secs, nanos)| Time::unix_epoch() + Duration::new(secs, nanos))
+}
+
+/// SHA-256 hashes
+pub fn hash() -> impl Strategy<Value = Hash> {
+    any::<[u8; 32]>().prop_map(Hash::Sha256)
+}
+
+/// Transaction hashes
+pub fn tx_hash() -> impl Strategy<Value = transaction::Hash> {
+    any::<[u8; 32]>().prop_map(transaction::Hash::new)
+}
+
+/// ABCI events with up to 3 attributes
+///
+/// Attribute keys and values always contain a character outside the base64
+/// alphabet (`_` and a space respectively), as strings which are valid
+/// base64 would be decoded when parsed, and hence not round-trip.
+pub fn event() -> impl Strategy<Value = abci::Event> {
+    let attribute = (
+        "[a-z]{0,4}_[a-z_]{0,3}",
+        "[ -~]{0,8} [ -~]{0,7}",
+        option::of(any::<bool>()),
+    )
+        .prop_map(|(key, value, index)| Tag {
+            key: key.parse().unwrap(),
+            value: value.parse().unwrap(),
+            index,
+        });
+
+    ("[a-z_]{1,8}", vec(attribute, 0..=3)).prop_map(|(type_str, attributes)| abci::Event {
+        type_str,
+        attributes,
+    })
+}
+
+/// Results of executing a transaction
+pub fn deliver_tx() -> impl Strategy<Value = DeliverTx> {
+    (
+        code(),
+        data(),
+        log(),
+        "[ -~]{0,16}",
+        any::<u32>(),
+        any::<u32>(),
+        vec(event(), 0..=2),
+        "[a-z]{0,8}",
+    )
+        .prop_map(
+            |(code, data, log, info, gas_wanted, gas_used, events, codespace)| {
+                // Some fields can only be built by deserializing them
+                serde_json::from_value(json!({
+                    "code": code,
+                    "data": data,
+                    "log": log,
+                    "info": info,
+                    "gasWanted": gas_wanted.to_string(),
+                    "gasUsed": gas_used.to_string(),
+                    "events": events,
+                    "codespace": codespace,
+                }))
+                .unwrap()
+            },
+        )
+}
+
+/// Validators of a set of 1 to 7, with their keys generated from their names
+pub fn validators() -> impl Strategy<Value = Vec<validator::Info>> {
+    vec("[a-z]{1,8}", 1..8).prop_map(|names| {
+        names
+            .iter()
+            .map(|name| testgen::Validator::new(name).generate().unwrap())
+            .collect()
+    })
+}
+
+/// Information about nodes, as reported by themselves or their peers
+pub fn node_info() -> impl Strategy<Value = node::Info> {
+    (
+        (any::<u64>(), any::<u64>(), any::<u64>()),
+        any::<[u8; 20]>(),
+        any::<u16>(),
+        "[a-z]{1,8}-[0-9]{1,3}",
+        "[0-9]{1,2}\\.[0-9]{1,2}\\.[0-9]{1,2}",
+        "([0-9a-f]{2}){0,8}",
+        "[a-z]{1,16}",
+        any::<bool>(),
+    )
+        .prop_map(
+            |((p2p, block, app), id, port, network, version, channels, moniker, tx_index)| {
+                let id: String = id.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+                // Some fields can only be built by deserializing them
+                serde_json::from_value(json!({
+                    "protocol_version": {
+                        "p2p": p2p.to_string(),
+                        "block": block.to_string(),
+                        "app": app.to_string(),
+                    },
+                    "id": id,
+                    "listen_addr": format!("tcp://0.0.0.0:{}", port),
+                    "network": network,
+                    "version": version,
+                    "channels": channels,
+                    "moniker": moniker,
+                    "other": {
+                        "tx_index": if tx_index { "on" } else { "off" },
+                        "rpc_address": "tcp://0.0.0.0:26657",
+                    },
+                }))
+                .unwrap()
+            },
+        )
+}
+
+/// `/status` responses, of nodes which may have no blocks yet
+pub fn status() -> impl Strategy<Value = status::Response> {
+    (
+        node_info(),
+        option::of(hash()),
+        option::of(hash()),
+        height(),
+        time(),
+        any::<bool>(),
+        validators(),
+    )
+        .prop_map(
+            |(node_info, block_hash, app_hash, height, time, catching_up, mut validators)| {
+                status::Response {
+                    node_info,
+                    sync_info: status::SyncInfo {
+                        latest_block_hash: block_hash,
+                        latest_app_hash: app_hash,
+                        latest_block_height: height,
+                        latest_block_time: time,
+                        catching_up,
+                    },
+                    validator_info: validators.remove(0),
+                }
+            },
+        )
+}
+
+/// Transfer rate monitors of peer connections
+fn monitor() -> impl Strategy<Value = serde_json::Value> {
+    (
+        any::<bool>(),
+        time(),
+        any::<u64>(),
+        any::<u64>(),
+        vec(any::<u64>(), 8),
+        0..=100u64,
+    )
+        .prop_map(|(active, start, duration, idle, stats, progress)| {
+            json!({
+                "Active": active,
+                "Start": start,
+                "Duration": duration.to_string(),
+                "Idle": idle.to_string(),
+                "Bytes": stats[0].to_string(),
+                "Samples": stats[1].to_string(),
+                "InstRate": stats[2].to_string(),
+                "CurRate": stats[3].to_string(),
+                "AvgRate": stats[4].to_string(),
+                "PeakRate": stats[5].to_string(),
+                "BytesRem": stats[6].to_string(),
+                "TimeRem": stats[7].to_string(),
+                "Progress": progress,
+            })
+        })
+}
+
+/// Peers of `/net_info` responses
+pub fn peer_info() -> impl Strategy<Value = net_info::PeerInfo> {
+    let channel = (any::<u8>(), vec(any::<u64>(), 4)).prop_map(|(id, stats)| {
+        json!({
+            "ID": id,
+            "SendQueueCapacity": stats[0].to_string(),
+            "SendQueueSize": stats[1].to_string(),
+            "Priority": stats[2].to_string(),
+            "RecentlySent": stats[3].to_string(),
+        })
+    });
+
+    (
+        node_info(),
+        any::<bool>(),
+        any::<u64>(),
+        monitor(),
+        monitor(),
+        vec(channel, 0..=4),
+        any::<IpAddr>(),
+    )
+        .prop_map(
+            |(node_info, is_outbound, duration, send, recv, channels, remote_ip)| {
+                // Some fields can only be built by deserializing them
+                let connection_status = serde_json::from_value(json!({
+                    "Duration": duration.to_string(),
+                    "SendMonitor": send,
+                    "RecvMonitor": recv,
+                    "Channels": channels,
+                }))
+                .unwrap();
+
+                net_info::PeerInfo {
+                    node_info,
+                    is_outbound,
+                    connection_status,
+                    remote_ip,
+                }
+            },
+        )
+}
+
+/// `/net_info` responses with up to 3 peers
+pub fn net_info() -> impl Strategy<Value = net_info::Response> {
+    (
+        any::<bool>(),
+        vec(any::<u16>(), 0..=2),
+        vec(peer_info(), 0..=3),
+    )
+        .prop_map(|(listening, ports, peers)| {
+            let listeners: Vec<_> = ports
+                .iter()
+                .map(|port| format!("Listener(@tcp://0.0.0.0:{})", port))
+                .collect();
+
+            net_info::Response {
+                listening,
+                listeners: serde_json::from_value(json!(listeners)).unwrap(),
+                n_peers: peers.len() as u64,
+                peers,
+            }
+        })
+}
+
+/// `/broadcast_tx_async` responses
+pub fn broadcast_tx_async() -> impl Strategy<Value = broadcast::tx_async::Response> {
+    (code(), data(), log(), tx_hash()).prop_map(|(code, data, log, hash)| {
+        broadcast::tx_async::Response {
+            code,
+            data,
+            log,
+            hash,
+        }
+    })
+}
+
+/// `/broadcast_tx_sync` responses
+pub fn broadcast_tx_sync() -> impl Strategy<Value = broadcast::tx_sync::Response> {
+    (code(), data(), log(), tx_hash()).prop_map(|(code, data, log, hash)| {
+        broadcast::tx_sync::Response {
+            code,
+            data,
+            log,
+            hash,
+        }
+    })
+}
+
+/// `CheckTx` and `DeliverTx` results of `/broadcast_tx_commit` responses
+pub fn tx_commit_result() -> impl Strategy<Value = broadcast::tx_commit::TxResult> {
+    (code(), option::of(data()), log())
+        .prop_map(|(code, data, log)| broadcast::tx_commit::TxResult { code, data, log })
+}
+
+/// `/broadcast_tx_commit` responses
+pub fn broadcast_tx_commit() -> impl Strategy<Value = broadcast::tx_commit::Response> {
+    (tx_commit_result(), tx_commit_result(), tx_hash(), height()).prop_map(
+        |(check_tx, deliver_tx, hash, height)| broadcast::tx_commit::Response {
+            check_tx,
+            deliver_tx,
+            hash,
+            height,
+        },
+    )
+}
+
+/// `/block_results` responses, without validator or consensus parameter
+/// updates
+pub fn block_results() -> impl Strategy<Value = block_results::Response> {
+    (
+        height(),
+        option::of(vec(deliver_tx(), 0..=3)),
+        option::of(vec(event(), 0..=2)),
+        option::of(vec(event(), 0..=2)),
+    )
+        .prop_map(
+            |(height, txs_results, begin_block_events, end_block_events)| block_results::Response {
+                height,
+                txs_results,
+                begin_block_events,
+                end_block_events,
+                validator_updates: Vec::new(),
+                consensus_param_updates: None,
+            },
+        )
+}
+
+/// `/validators` responses
+pub fn validators_response() -> impl Strategy<Value = validators::Response> {
+    (height(), validators()).prop_map(|(block_height, validators)| validators::Response {
+        block_height,
+        validators,
+    })
+}
+
+/// `/block` responses for blocks of up to 3 transactions and no evidence
+///
+/// The last commit is signed by the block's validators, some of whose
+/// signatures may be absent. Blocks at height 1 have no last commit.
+pub fn block() -> impl Strategy<Value = block_endpoint::Response> {
+    (
+        height(),
+        vec("[a-z]{1,8}", 1..8),
+        vec(vec(any::<u8>(), 1..32), 0..=3),
+        any::<u8>(),
+    )
+        .prop_map(|(height, names, txs, absent)| {
+            let validators: Vec<_> = names
+                .iter()
+                .map(|name| testgen::Validator::new(name))
+                .collect();
+            let header = testgen::Header::new(&validators)
+                .height(height.value())
+                .generate()
+                .unwrap();
+
+            let last_commit = if height.value() > 1 {
+                let previous = testgen::Header::new(&validators).height(height.value() - 1);
+                let commit = testgen::Commit::new(previous, 1).generate().unwrap();
+
+                // Bit `i` of `absent` drops the signature of validator `i`
+                let signatures: Vec<_> = commit
+                    .signatures
+                    .iter()
+                    .enumerate()
+                    .map(|(i, signature)| {
+                        if absent & (1 << i) != 0 {
+                            block::CommitSig::BlockIDFlagAbsent
+                        } else {
+                            signature.clone()
+                        }
+                    })
+                    .collect();
+
+                Some(block::Commit {
+                    signatures: block::CommitSigs::new(signatures),
+                    ..commit
+                })
+            } else {
+                None
+            };
+
+            let block_id = block::Id::new(header.hash(), None);
+            let txs: Vec<_> = txs.into_iter().map(Transaction::new).collect();
+
+            block_endpoint::Response {
+                block_id,
+                block: Block {
+                    header,
+                    data: transaction::Data::new(txs),
+                    evidence: evidence::Data::new(Vec::new()),
+                    last_commit,
+                },
+            }
+        })
+}
@@ -0,0 +1,274 @@
+//! JSONRPC batches, i.e. several requests sent as one JSON array
+
+use crate::request::into_json_with_id;
+use crate::{Error, Id, IdProvider, RandomUuidProvider, Request, Response, Version};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::sync::Arc;
+
+/// Batch of requests, of any mix of types, sent to the node as a JSON array
+#[derive(Debug)]
+pub struct BatchRequest {
+    /// Serialized request wrappers, in the order they were added
+    entries: Vec<Box<RawValue>>,
+
+    /// Generates the ID of each request
+    id_provider: Arc<dyn IdProvider>,
+}
+
+impl Default for BatchRequest {
+    fn default() -> Self {
+        Self::with_shared_id_provider(Arc::new(RandomUuidProvider))
+    }
+}
+
+impl BatchRequest {
+    /// Create an empty batch
+    ///
+    /// Use `Client::batch_request` instead to draw request IDs from the
+    /// client's `IdProvider`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty batch whose request IDs are generated by the given
+    /// provider, instead of being random UUIDs
+    pub fn with_id_provider(id_provider: impl IdProvider + 'static) -> Self {
+        Self::with_shared_id_provider(Arc::new(id_provider))
+    }
+
+    /// Create an empty batch whose request IDs are generated by a provider
+    /// shared with a client
+    pub(crate) fn with_shared_id_provider(id_provider: Arc<dyn IdProvider>) -> Self {
+        Self {
+            entries: Vec::new(),
+            id_provider,
+        }
+    }
+
+    /// Add a request to this batch, returning the ID under which its response
+    /// can be taken from the `BatchResponse`
+    pub fn push<R: Request>(&mut self, request: R) -> Id {
+        let id = self.id_provider.next_id();
+        let json = into_json_with_id(request, id.clone());
+        self.entries.push(RawValue::from_string(json).unwrap());
+        id
+    }
+
+    /// Number of requests in this batch
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Does this batch contain no requests?
+    ///
+    /// Nodes reject empty batches as invalid requests.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Serialize this batch as a JSON array
+    pub fn into_json(self) -> String {
+        serde_json::to_string(&self.entries).unwrap()
+    }
+}
+
+/// Responses to a `BatchRequest`
+///
+/// The result of each entry is only parsed once it is taken by ID, so the
+/// batch may contain responses of any mix of types.
+#[derive(Debug)]
+pub struct BatchResponse {
+    /// Responses not taken yet, in the order the node sent them
+    entries: Vec<Entry>,
+}
+
+/// One response in a batch, with its result left unparsed
+#[derive(Debug, Deserialize)]
+struct Entry {
+    /// JSONRPC version
+    jsonrpc: Version,
+
+    /// Identifier of the request this is a response to
+    id: Id,
+
+    /// Results of request (if successful)
+    result: Option<Box<RawValue>>,
+
+    /// Error message if unsuccessful
+    error: Option<Error>,
+}
+
+impl BatchResponse {
+    /// Parse the responses to a batch from a JSON string
+    ///
+    /// A single response object is accepted as a batch of one, unless it is
+    /// an error without an ID, which means the node rejected the batch as a
+    /// whole (e.g. because it was empty) and is returned as `Err`.
+    pub fn from_string(response: impl AsRef<[u8]>) -> Result<Self, Error> {
+        let response = response.as_ref();
+
+        // Some servers collapse one-element batches, and errors about the
+        // batch as a whole, to a single object. This is checked up front as
+        // raw results cannot be deserialized through an untagged enum.
+        let is_array = response
+            .iter()
+            .find(|byte| !byte.is_ascii_whitespace())
+            .map_or(false, |byte| *byte == b'[');
+
+        let entries = if is_array {
+            serde_json::from_slice(response)?
+        } else {
+            match serde_json::from_slice(response)? {
+                Entry {
+                    id: Id::None,
+                    error: Some(error),
+                    ..
+                } => return Err(error.sent_by_node()),
+                entry => vec![entry],
+            }
+        };
+
+        Ok(Self { entries })
+    }
+
+    /// Number of responses not taken yet
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Have all responses been taken?
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Take the response to the request with the given ID, parsing it as `R`
+    ///
+    /// If the node sent several responses with the same ID, they are taken
+    /// in the order it sent them.
+    pub fn take<R: Response>(&mut self, id: &Id) -> Result<R, Error> {
+        let position = self
+            .entries
+            .iter()
+            .position(|entry| entry.id == *id)
+            .ok_or_else(|| {
+                Error::parse_error(format!(
+                    "batch response has no entry for request {}",
+                    serde_json::to_string(id).unwrap()
+                ))
+            })?;
+
+        let entry = self.entries.remove(position);
+        entry.jsonrpc.ensure_supported()?;
+
+        match (entry.error, entry.result) {
+            (Some(error), _) => Err(error.sent_by_node()),
+            (None, Some(result)) => Ok(serde_json::from_str(result.get())?),
+            (None, None) => Err(Error::parse_error(
+                "server returned malformatted JSON (no 'result' or 'error')",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint::{abci_info, health};
+    use crate::error::Code;
+    use crate::SequentialProvider;
+
+    #[test]
+    fn request_entries_keep_their_ids() {
+        let mut batch = BatchRequest::new();
+        assert!(batch.is_empty());
+
+        let health_id = batch.push(health::Request);
+        let abci_info_id = batch.push(abci_info::Request);
+        assert_ne!(health_id, abci_info_id);
+        assert_eq!(batch.len(), 2);
+
+        let json: serde_json::Value = serde_json::from_str(&batch.into_json()).unwrap();
+        let entries = json.as_array().unwrap();
+        assert_eq!(entries[0]["method"], "health");
+        assert_eq!(entries[0]["id"], serde_json::to_value(&health_id).unwrap());
+        assert_eq!(entries[1]["method"], "abci_info");
+        assert_eq!(
+            entries[1]["id"],
+            serde_json::to_value(&abci_info_id).unwrap()
+        );
+    }
+
+    #[test]
+    fn request_ids_from_provider() {
+        let mut batch = BatchRequest::with_id_provider(SequentialProvider::new(5));
+        assert_eq!(batch.push(health::Request), Id::Num(5));
+        assert_eq!(batch.push(abci_info::Request), Id::Num(6));
+
+        let json: serde_json::Value = serde_json::from_str(&batch.into_json()).unwrap();
+        assert_eq!(json[0]["id"], 5);
+        assert_eq!(json[1]["id"], 6);
+    }
+
+    #[test]
+    fn mixed_success_and_error() {
+        let mut batch = BatchResponse::from_string(
+            r#"[
+                {"jsonrpc":"2.0","id":"2","error":{"code":-32601,"message":"Method not found","data":""}},
+                {"jsonrpc":"2.0","id":"1","result":{}},
+                {"jsonrpc":"2.0","id":"3","result":{"response":{"data":"GaiaApp","last_block_height":"488120","last_block_app_hash":"2LnCw0fN+Zq/gs5SOuya/GRHUmtWftAqAkTUuoxl4g4="}}}
+            ]"#,
+        )
+        .unwrap();
+
+        let id = |id: &str| Id::Str(id.to_owned());
+
+        // Responses are taken by ID, regardless of their order
+        batch.take::<health::Response>(&id("1")).unwrap();
+        let abci_info = batch.take::<abci_info::Response>(&id("3")).unwrap();
+        assert_eq!(abci_info.response.data, "GaiaApp");
+        let error = batch.take::<health::Response>(&id("2")).unwrap_err();
+        assert_eq!(error.code(), Code::MethodNotFound);
+        assert!(batch.is_empty());
+
+        // Each response can only be taken once
+        let error = batch.take::<health::Response>(&id("1")).unwrap_err();
+        assert!(error.is_parse_error());
+    }
+
+    #[test]
+    fn duplicate_ids() {
+        let mut batch = BatchResponse::from_string(
+            r#"[
+                {"jsonrpc":"2.0","id":1,"error":{"code":-32603,"message":"Internal error","data":"first"}},
+                {"jsonrpc":"2.0","id":1,"error":{"code":-32603,"message":"Internal error","data":"second"}}
+            ]"#,
+        )
+        .unwrap();
+
+        let error = batch.take::<health::Response>(&Id::Num(1)).unwrap_err();
+        assert_eq!(error.data(), Some("first"));
+        let error = batch.take::<health::Response>(&Id::Num(1)).unwrap_err();
+        assert_eq!(error.data(), Some("second"));
+    }
+
+    #[test]
+    fn single_object_and_empty_batches() {
+        // A collapsed one-element batch
+        let mut batch =
+            BatchResponse::from_string(r#"{"jsonrpc":"2.0","id":"1","result":{}}"#).unwrap();
+        assert_eq!(batch.len(), 1);
+        batch
+            .take::<health::Response>(&Id::Str("1".to_owned()))
+            .unwrap();
+
+        // The node rejecting the batch as a whole
+        let error = BatchResponse::from_string(
+            r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32600,"message":"Invalid Request","data":"empty batch"}}"#,
+        )
+        .unwrap_err();
+        assert_eq!(error.code(), Code::InvalidRequest);
+
+        assert!(BatchResponse::from_string("[]").unwrap().is_empty());
+    }
+}
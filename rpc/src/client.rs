@@ -1,17 +1,49 @@
 //! Tendermint RPC client
 
-use bytes::buf::ext::BufExt;
-use hyper::header;
+use futures::stream::{self, StreamExt};
+use hyper::{header, StatusCode};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, Semaphore};
 
 use tendermint::abci::{self, Transaction};
-use tendermint::block::Height;
+use tendermint::block::{signed_header::SignedHeader, Height};
+use tendermint::chain;
 use tendermint::evidence::Evidence;
 use tendermint::net;
 use tendermint::Genesis;
 
-use crate::{endpoint::*, Error, Request, Response};
+use crate::batch::BatchRequest;
+use crate::error::Kind;
+use crate::request::into_json_with_id;
+use crate::response::{parse_response, WithRaw};
+#[cfg(feature = "metrics")]
+use crate::Method;
+use crate::{endpoint::*, CompatMode, Error, IdProvider, RandomUuidProvider, Request, Response};
 
+pub use self::builder::{ClientBuilder, EventListenerBuilder};
+use self::event_listener::EventListener;
+use self::rate_limit::RateLimiter;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod builder;
 pub mod event_listener;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+mod rate_limit;
+
+/// Maximum number of commits fetched concurrently by `Client::commits`
+const MAX_CONCURRENT_COMMITS: usize = 4;
+
+/// Interval at which `Client::wait_for_tx` polls `/tx`
+const TX_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Default `User-Agent` sent to the node
+pub(crate) const USER_AGENT: &str = concat!("tendermint-rpc/", env!("CARGO_PKG_VERSION"));
 
 /// Tendermint RPC client.
 ///
@@ -20,12 +52,149 @@ pub mod event_listener;
 pub struct Client {
     /// Address of the RPC server
     address: net::Address,
+
+    /// `User-Agent` sent with each request
+    user_agent: String,
+
+    /// Addresses to connect to instead of resolving the given hosts
+    host_overrides: HashMap<String, IpAddr>,
+
+    /// Limit on the number of concurrent requests (shared between clones)
+    in_flight: Option<Arc<Semaphore>>,
+
+    /// Limit on the rate at which requests are sent (shared between clones)
+    rate_limit: Option<Arc<RateLimiter>>,
+
+    /// Chain ID of the node, once fetched (shared between clones)
+    chain_id: Arc<Mutex<Option<chain::Id>>>,
+
+    /// Chain ID the node must be on for requests to be sent
+    expected_chain_id: Option<chain::Id>,
+
+    /// Generates the ID of each request (shared between clones)
+    id_provider: Arc<dyn IdProvider>,
+
+    /// Accept responses which are not valid JSONRPC 2.0 responses
+    lenient: bool,
+
+    /// Tendermint version whose encodings responses are decoded with
+    compat: CompatMode,
+
+    /// Latency of requests, by method (shared between clones)
+    #[cfg(feature = "metrics")]
+    metrics: Arc<metrics::Metrics>,
 }
 
 impl Client {
     /// Create a new Tendermint RPC client, connecting to the given address
     pub fn new(address: net::Address) -> Self {
-        Self { address }
+        Self {
+            address,
+            user_agent: USER_AGENT.to_owned(),
+            host_overrides: HashMap::new(),
+            in_flight: None,
+            rate_limit: None,
+            chain_id: Arc::new(Mutex::new(None)),
+            expected_chain_id: None,
+            id_provider: Arc::new(RandomUuidProvider),
+            lenient: false,
+            compat: CompatMode::default(),
+            #[cfg(feature = "metrics")]
+            metrics: Arc::default(),
+        }
+    }
+
+    /// Start building a client connecting to the given address, with its
+    /// options validated when it is built
+    pub fn builder(address: net::Address) -> ClientBuilder {
+        ClientBuilder::new(address)
+    }
+
+    /// Identify this client to the node with the given `User-Agent`, instead
+    /// of `tendermint-rpc/<version>`
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Connect to `ip` whenever `host` would be connected to, instead of
+    /// resolving it, e.g. to pin a specific node behind a shared hostname.
+    ///
+    /// Requests, and event listeners opened with `Client::event_listener`,
+    /// still name `host` in their `Host` header.
+    pub fn with_host_override(mut self, host: impl Into<String>, ip: IpAddr) -> Self {
+        self.host_overrides.insert(host.into(), ip);
+        self
+    }
+
+    /// Generate request IDs with the given provider, instead of using random
+    /// UUIDs
+    pub fn with_id_provider(mut self, id_provider: impl IdProvider + 'static) -> Self {
+        self.id_provider = Arc::new(id_provider);
+        self
+    }
+
+    /// Accept responses which are not valid JSONRPC 2.0 responses, e.g. which
+    /// lack a `jsonrpc` version or have another ID than the request, as long
+    /// as their result or error can be parsed.
+    ///
+    /// Only needed for servers or proxies known to send such responses.
+    pub fn with_lenient_responses(mut self) -> Self {
+        self.lenient = true;
+        self
+    }
+
+    /// Only talk to a node on the given chain.
+    ///
+    /// The node's chain ID is checked before the first request is sent, and
+    /// every request fails with a `Code::ChainIdMismatch` error if it is on
+    /// another chain.
+    pub fn with_expected_chain_id(mut self, chain_id: chain::Id) -> Self {
+        self.expected_chain_id = Some(chain_id);
+        self
+    }
+
+    /// Limit the number of requests this client (and its clones) may have in
+    /// flight at any one time.
+    ///
+    /// Requests beyond the limit wait until an earlier request completes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero, as no request could ever be sent.
+    pub fn with_max_in_flight_requests(mut self, limit: usize) -> Self {
+        assert!(
+            limit > 0,
+            "limit on requests in flight must be at least one"
+        );
+        self.in_flight = Some(Arc::new(Semaphore::new(limit)));
+        self
+    }
+
+    /// Limit the rate at which this client (and its clones) send requests to
+    /// `requests_per_second`.
+    ///
+    /// Bursts of up to `requests_per_second` requests are sent immediately,
+    /// after which requests are queued and sent at the configured rate.
+    /// Subscriptions made on listeners opened by `event_listener` count as
+    /// requests.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests_per_second` is zero.
+    pub fn with_rate_limit(mut self, requests_per_second: u32) -> Self {
+        self.rate_limit = Some(Arc::new(RateLimiter::new(requests_per_second)));
+        self
+    }
+
+    /// Decode responses with the encodings of the given Tendermint version,
+    /// instead of those of v0.34.
+    ///
+    /// Event listeners opened with `Client::event_listener` use the same
+    /// mode.
+    pub fn with_compat_mode(mut self, compat: CompatMode) -> Self {
+        self.compat = compat;
+        self
     }
 
     /// `/abci_info`: get information about the ABCI application.
@@ -117,6 +286,20 @@ impl Client {
         self.perform(commit::Request::new(height.into())).await
     }
 
+    /// `/commit`: get the signed headers for all heights in `range`, in
+    /// height order.
+    ///
+    /// Commits are fetched concurrently. Each height gets its own result, so
+    /// heights the node no longer has (e.g. because they were pruned) do not
+    /// prevent the others from being returned.
+    pub async fn commits(&self, range: RangeInclusive<Height>) -> Vec<Result<SignedHeader, Error>> {
+        stream::iter(range.start().value()..=range.end().value())
+            .map(|height| async move { Ok(self.commit(height).await?.signed_header) })
+            .buffered(MAX_CONCURRENT_COMMITS)
+            .collect()
+            .await
+    }
+
     /// `/validators`: get validators a given height.
     pub async fn validators<H>(&self, height: H) -> Result<validators::Response, Error>
     where
@@ -154,46 +337,286 @@ impl Client {
         self.perform(status::Request).await
     }
 
+    /// Get the ID of the chain the node is on.
+    ///
+    /// The ID is fetched from `/status` on first use and cached for the
+    /// lifetime of this client (and its clones).
+    pub async fn chain_id(&self) -> Result<chain::Id, Error> {
+        let mut chain_id = self.chain_id.lock().await;
+
+        if let Some(id) = *chain_id {
+            return Ok(id);
+        }
+
+        // Sent directly, as `perform` may itself be waiting on the chain ID
+        let id = self.send(status::Request).await?.node_info.network;
+        *chain_id = Some(id);
+        Ok(id)
+    }
+
+    /// `/tx`: get a committed transaction by its hash, along with a proof of
+    /// its inclusion in its block if `prove` is set.
+    pub async fn tx(
+        &self,
+        hash: abci::transaction::Hash,
+        prove: bool,
+    ) -> Result<tx::Response, Error> {
+        self.perform(tx::Request::new(hash, prove)).await
+    }
+
+    /// `/tx_search`: search for committed transactions matching `query`,
+    /// getting page `page` (starting at 1) of `per_page` results, along with
+    /// proofs of their inclusion if `prove` is set.
+    ///
+    /// Fails with `Code::InvalidParams`, without sending the request, if
+    /// `per_page` exceeds `tx_search::MAX_PER_PAGE`, which the node would
+    /// silently clamp it to.
+    pub async fn tx_search(
+        &self,
+        query: impl Into<String>,
+        prove: bool,
+        page: u32,
+        per_page: u32,
+        order_by: tx_search::Order,
+    ) -> Result<tx_search::Response, Error> {
+        tx_search::check_per_page(per_page)?;
+        self.perform(tx_search::Request::new(
+            query, prove, page, per_page, order_by,
+        ))
+        .await
+    }
+
+    /// Wait for the transaction with the given hash to be committed, e.g.
+    /// after broadcasting it with `broadcast_tx_async`.
+    ///
+    /// The transaction is subscribed to over the websocket, and looked up
+    /// with `/tx` once the subscription is made, in case it was committed
+    /// before. If the websocket cannot be used, `/tx` is polled instead.
+    ///
+    /// Fails with a timeout error if the transaction is not committed within
+    /// `timeout`, or with any other error the node reports, e.g. if it does
+    /// not index transactions.
+    pub async fn wait_for_tx(
+        &self,
+        hash: abci::transaction::Hash,
+        timeout: Duration,
+    ) -> Result<tx::Response, Error> {
+        let wait = async {
+            match self.tx_listener(hash).await {
+                Ok(mut listener) => {
+                    match self.tx(hash, false).await {
+                        Err(e) if e.kind() == Kind::TxNotFound => (),
+                        result => return result,
+                    }
+
+                    // The only events subscribed to are those of the
+                    // transaction, and any error means that the websocket
+                    // can no longer be relied upon
+                    loop {
+                        match listener.get_event().await {
+                            Ok(Some(_)) => break,
+                            Ok(None) => continue,
+                            Err(e) => {
+                                log::debug!("polling for tx {} instead: {}", hash, e);
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(e) => log::debug!("polling for tx {}: {}", hash, e),
+            }
+
+            // Also covers the transaction being indexed shortly after its
+            // event is published
+            self.poll_tx(hash).await
+        };
+
+        tokio::time::timeout(timeout, wait).await.map_err(|_| {
+            Error::timeout(format!("tx {} not committed within {:?}", hash, timeout))
+        })?
+    }
+
+    /// Open an event listener subscribed to the transaction with the given
+    /// hash
+    async fn tx_listener(&self, hash: abci::transaction::Hash) -> Result<EventListener, Error> {
+        let mut listener = self.event_listener().await?;
+        listener
+            .subscribe_query(format!("tm.event='Tx' AND tx.hash='{}'", hash))
+            .await?;
+        Ok(listener)
+    }
+
+    /// Poll `/tx` until the transaction with the given hash is found
+    async fn poll_tx(&self, hash: abci::transaction::Hash) -> Result<tx::Response, Error> {
+        loop {
+            match self.tx(hash, false).await {
+                Err(e) if e.kind() == Kind::TxNotFound => {
+                    tokio::time::delay_for(TX_POLL_INTERVAL).await
+                }
+                result => return result,
+            }
+        }
+    }
+
     /// `/broadcast_evidence`: broadcast an evidence.
     pub async fn broadcast_evidence(&self, e: Evidence) -> Result<evidence::Response, Error> {
         self.perform(evidence::Request::new(e)).await
     }
 
+    /// Open an event listener on the websocket of the same node, with this
+    /// client's `User-Agent`, host overrides, `IdProvider` and `CompatMode`,
+    /// whose subscriptions count against this client's rate limit
+    pub async fn event_listener(&self) -> Result<EventListener, Error> {
+        let (host, port) = self.host_and_port()?;
+        let connect_to = self
+            .host_overrides
+            .get(host)
+            .map(|ip| SocketAddr::new(*ip, port));
+
+        let mut listener = EventListener::open(host, port, connect_to, &self.user_agent)
+            .await?
+            .with_rate_limit(self.rate_limit.clone())
+            .with_compat_mode(self.compat);
+        listener.id_provider = self.id_provider.clone();
+        Ok(listener)
+    }
+
+    /// Start an empty batch of requests whose IDs are drawn from this
+    /// client's `IdProvider`, like those of its other requests
+    pub fn batch_request(&self) -> BatchRequest {
+        BatchRequest::with_shared_id_provider(self.id_provider.clone())
+    }
+
+    /// Latency of the requests made by this client (and its clones) so far,
+    /// by method.
+    ///
+    /// Only requests which got a response from the node are included, timed
+    /// from sending the request until the response was read in full, i.e.
+    /// excluding time spent waiting on the rate limit or for other requests
+    /// to complete.
+    #[cfg(feature = "metrics")]
+    pub fn metrics_snapshot(&self) -> HashMap<Method, metrics::Latency> {
+        self.metrics.snapshot()
+    }
+
+    /// Host and port of the node
+    fn host_and_port(&self) -> Result<(&str, u16), Error> {
+        match &self.address {
+            net::Address::Tcp { host, port, .. } => Ok((host, *port)),
+            other => Err(Error::invalid_params(&format!(
+                "invalid RPC address: {:?}",
+                other
+            ))),
+        }
+    }
+
     /// Perform a request against the RPC endpoint
     pub async fn perform<R>(&self, request: R) -> Result<R::Response, Error>
     where
         R: Request,
     {
-        let request_body = request.into_json();
-
-        let (host, port) = match &self.address {
-            net::Address::Tcp { host, port, .. } => (host, port),
-            other => {
-                return Err(Error::invalid_params(&format!(
-                    "invalid RPC address: {:?}",
-                    other
-                )))
+        self.check_chain_id().await?;
+        self.send(request).await
+    }
+
+    /// Perform a request against the RPC endpoint, keeping the response body
+    /// exactly as the node sent it alongside the parsed response
+    pub async fn perform_with_raw<R>(&self, request: R) -> Result<WithRaw<R::Response>, Error>
+    where
+        R: Request,
+    {
+        self.check_chain_id().await?;
+        self.send_with_raw(request).await
+    }
+
+    /// Ensure the node is on the expected chain, if any
+    async fn check_chain_id(&self) -> Result<(), Error> {
+        if let Some(expected) = self.expected_chain_id {
+            let actual = self.chain_id().await?;
+
+            if actual != expected {
+                return Err(Error::chain_id_mismatch(expected, actual));
             }
+        }
+
+        Ok(())
+    }
+
+    /// Send a request to the RPC endpoint, without checking the node's chain
+    async fn send<R>(&self, request: R) -> Result<R::Response, Error>
+    where
+        R: Request,
+    {
+        // The body is read in full before parsing either way, so dropping it
+        // here costs nothing over not capturing it
+        Ok(self.send_with_raw(request).await?.parsed)
+    }
+
+    /// Send a request to the RPC endpoint, without checking the node's chain,
+    /// and keep the response body
+    async fn send_with_raw<R>(&self, request: R) -> Result<WithRaw<R::Response>, Error>
+    where
+        R: Request,
+    {
+        if let Some(rate_limit) = &self.rate_limit {
+            rate_limit.acquire().await;
+        }
+
+        let _permit = match &self.in_flight {
+            Some(semaphore) => Some(semaphore.acquire().await),
+            None => None,
+        };
+
+        let method = request.method();
+        let request_id = self.id_provider.next_id();
+        let request_body = into_json_with_id(request, request_id.clone());
+
+        let (host, port) = self.host_and_port()?;
+        let authority = format!("{}:{}", host, port);
+        let uri = match self.host_overrides.get(host) {
+            Some(ip) => format!("http://{}/", SocketAddr::new(*ip, port)),
+            None => format!("http://{}/", authority),
         };
 
-        let mut request = hyper::Request::builder()
+        let request = hyper::Request::builder()
             .method("POST")
-            .uri(&format!("http://{}:{}/", host, port))
+            .uri(&uri)
+            .header(header::HOST, authority.as_str())
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::USER_AGENT, self.user_agent.as_str())
             .body(hyper::Body::from(request_body.into_bytes()))?;
 
-        {
-            let headers = request.headers_mut();
-            headers.insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
-            headers.insert(
-                header::USER_AGENT,
-                format!("tendermint.rs/{}", env!("CARGO_PKG_VERSION"))
-                    .parse()
-                    .unwrap(),
-            );
-        }
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+
         let http_client = hyper::Client::builder().build_http();
         let response = http_client.request(request).await?;
-        let response_body = hyper::body::aggregate(response.into_body()).await?;
-        R::Response::from_reader(response_body.reader())
+
+        // Tendermint sends JSONRPC errors with a variety of statuses, so only
+        // those of an overloaded or rate limiting node are treated as errors
+        if response.status() == StatusCode::TOO_MANY_REQUESTS
+            || response.status() == StatusCode::SERVICE_UNAVAILABLE
+        {
+            // Only the delay-seconds form of `Retry-After` is supported
+            let retry_after = response
+                .headers()
+                .get(header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+                .map(Duration::from_secs);
+
+            return Err(Error::server_busy(response.status(), retry_after));
+        }
+
+        let raw = hyper::body::to_bytes(response.into_body()).await?;
+
+        #[cfg(feature = "metrics")]
+        self.metrics.record(method, started.elapsed());
+
+        let parsed = self
+            .compat
+            .scope(|| parse_response(method, &request_id, &raw, self.lenient))?;
+
+        Ok(WithRaw { parsed, raw })
     }
 }
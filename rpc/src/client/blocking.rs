@@ -0,0 +1,328 @@
+//! Blocking Tendermint RPC client, for callers without an async runtime
+
+use std::future::Future;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::{self, Handle, Runtime};
+
+use tendermint::abci::{self, Transaction};
+use tendermint::block::{signed_header::SignedHeader, Height};
+use tendermint::chain;
+use tendermint::evidence::Evidence;
+use tendermint::net;
+use tendermint::Genesis;
+
+use super::event_listener::{EventListener, EventSubscription, ResultEvent};
+use super::Client;
+use crate::{endpoint::*, error::Code, Error, Request};
+
+/// Tendermint RPC client with blocking methods.
+///
+/// Drives a `Client` on a runtime of its own, so requests can be made from
+/// synchronous code such as CLI tools and scripts. Its methods panic if
+/// called from async code, which should use `Client` instead.
+///
+/// Clones share the runtime, whose single worker thread drives the
+/// connections, while each request runs on the thread which made it, so
+/// clones can be used concurrently from several threads.
+#[derive(Clone, Debug)]
+pub struct BlockingClient {
+    /// Client requests are sent with
+    client: Client,
+
+    /// Runtime requests are driven on (shared between clones)
+    runtime: Arc<Runtime>,
+}
+
+/// Alias of `BlockingClient`, which sends its requests over HTTP
+pub type HttpClient = BlockingClient;
+
+impl BlockingClient {
+    /// Create a new blocking Tendermint RPC client, connecting to the given
+    /// address
+    ///
+    /// # Panics
+    ///
+    /// Panics if the client's runtime cannot be started.
+    pub fn new(address: net::Address) -> Self {
+        Self::from_client(Client::new(address))
+    }
+
+    /// Create a new blocking client sending requests with the given client,
+    /// and hence with its options, such as its rate limit
+    ///
+    /// # Panics
+    ///
+    /// Panics if the client's runtime cannot be started.
+    pub fn from_client(client: Client) -> Self {
+        let runtime = runtime::Builder::new()
+            .threaded_scheduler()
+            .core_threads(1)
+            .enable_all()
+            .build()
+            .expect("failed to start runtime for blocking client");
+
+        Self {
+            client,
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    /// Run `future` to completion on this client's runtime
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        block_on(&self.runtime, future)
+    }
+
+    /// `/abci_info`: get information about the ABCI application.
+    pub fn abci_info(&self) -> Result<abci_info::AbciInfo, Error> {
+        self.block_on(self.client.abci_info())
+    }
+
+    /// `/abci_query`: query the ABCI application
+    pub fn abci_query(
+        &self,
+        path: Option<abci::Path>,
+        data: impl Into<Vec<u8>>,
+        height: Option<Height>,
+        prove: bool,
+    ) -> Result<abci_query::AbciQuery, Error> {
+        self.block_on(self.client.abci_query(path, data, height, prove))
+    }
+
+    /// `/block`: get block at a given height.
+    pub fn block(&self, height: impl Into<Height>) -> Result<block::Response, Error> {
+        self.block_on(self.client.block(height))
+    }
+
+    /// `/block`: get the latest block.
+    pub fn latest_block(&self) -> Result<block::Response, Error> {
+        self.block_on(self.client.latest_block())
+    }
+
+    /// `/block_results`: get ABCI results for a block at a particular height.
+    pub fn block_results(
+        &self,
+        height: impl Into<Height>,
+    ) -> Result<block_results::Response, Error> {
+        self.block_on(self.client.block_results(height))
+    }
+
+    /// `/block_results`: get ABCI results for the latest block.
+    pub fn latest_block_results(&self) -> Result<block_results::Response, Error> {
+        self.block_on(self.client.latest_block_results())
+    }
+
+    /// `/blockchain`: get block headers for `min` <= `height` <= `max`.
+    ///
+    /// Block headers are returned in descending order (highest first).
+    ///
+    /// Returns at most 20 items.
+    pub fn blockchain(
+        &self,
+        min: impl Into<Height>,
+        max: impl Into<Height>,
+    ) -> Result<blockchain::Response, Error> {
+        self.block_on(self.client.blockchain(min, max))
+    }
+
+    /// `/broadcast_tx_async`: broadcast a transaction, returning immediately.
+    pub fn broadcast_tx_async(
+        &self,
+        tx: Transaction,
+    ) -> Result<broadcast::tx_async::Response, Error> {
+        self.block_on(self.client.broadcast_tx_async(tx))
+    }
+
+    /// `/broadcast_tx_sync`: broadcast a transaction, returning the response
+    /// from `CheckTx`.
+    pub fn broadcast_tx_sync(
+        &self,
+        tx: Transaction,
+    ) -> Result<broadcast::tx_sync::Response, Error> {
+        self.block_on(self.client.broadcast_tx_sync(tx))
+    }
+
+    /// `/broadcast_tx_commit`: broadcast a transaction, returning the response
+    /// from `DeliverTx`.
+    pub fn broadcast_tx_commit(
+        &self,
+        tx: Transaction,
+    ) -> Result<broadcast::tx_commit::Response, Error> {
+        self.block_on(self.client.broadcast_tx_commit(tx))
+    }
+
+    /// `/commit`: get block commit at a given height.
+    pub fn commit(&self, height: impl Into<Height>) -> Result<commit::Response, Error> {
+        self.block_on(self.client.commit(height))
+    }
+
+    /// `/commit`: get the signed headers for all heights in `range`, in
+    /// height order.
+    pub fn commits(&self, range: RangeInclusive<Height>) -> Vec<Result<SignedHeader, Error>> {
+        self.block_on(self.client.commits(range))
+    }
+
+    /// `/validators`: get validators a given height.
+    pub fn validators(&self, height: impl Into<Height>) -> Result<validators::Response, Error> {
+        self.block_on(self.client.validators(height))
+    }
+
+    /// `/commit`: get the latest block commit
+    pub fn latest_commit(&self) -> Result<commit::Response, Error> {
+        self.block_on(self.client.latest_commit())
+    }
+
+    /// `/health`: get node health.
+    pub fn health(&self) -> Result<(), Error> {
+        self.block_on(self.client.health())
+    }
+
+    /// `/genesis`: get genesis file.
+    pub fn genesis(&self) -> Result<Genesis, Error> {
+        self.block_on(self.client.genesis())
+    }
+
+    /// `/net_info`: obtain information about P2P and other network connections.
+    pub fn net_info(&self) -> Result<net_info::Response, Error> {
+        self.block_on(self.client.net_info())
+    }
+
+    /// `/status`: get Tendermint status including node info, pubkey, latest
+    /// block hash, app hash, block height and time.
+    pub fn status(&self) -> Result<status::Response, Error> {
+        self.block_on(self.client.status())
+    }
+
+    /// Get the ID of the chain the node is on, see `Client::chain_id`.
+    pub fn chain_id(&self) -> Result<chain::Id, Error> {
+        self.block_on(self.client.chain_id())
+    }
+
+    /// `/tx`: get a committed transaction by its hash, along with a proof of
+    /// its inclusion in its block if `prove` is set.
+    pub fn tx(&self, hash: abci::transaction::Hash, prove: bool) -> Result<tx::Response, Error> {
+        self.block_on(self.client.tx(hash, prove))
+    }
+
+    /// `/tx_search`: search for committed transactions, see
+    /// `Client::tx_search`.
+    pub fn tx_search(
+        &self,
+        query: impl Into<String>,
+        prove: bool,
+        page: u32,
+        per_page: u32,
+        order_by: tx_search::Order,
+    ) -> Result<tx_search::Response, Error> {
+        self.block_on(
+            self.client
+                .tx_search(query, prove, page, per_page, order_by),
+        )
+    }
+
+    /// Wait for the transaction with the given hash to be committed, see
+    /// `Client::wait_for_tx`.
+    pub fn wait_for_tx(
+        &self,
+        hash: abci::transaction::Hash,
+        timeout: Duration,
+    ) -> Result<tx::Response, Error> {
+        self.block_on(self.client.wait_for_tx(hash, timeout))
+    }
+
+    /// `/broadcast_evidence`: broadcast an evidence.
+    pub fn broadcast_evidence(&self, e: Evidence) -> Result<evidence::Response, Error> {
+        self.block_on(self.client.broadcast_evidence(e))
+    }
+
+    /// Perform a request against the RPC endpoint
+    pub fn perform<R>(&self, request: R) -> Result<R::Response, Error>
+    where
+        R: Request,
+    {
+        self.block_on(self.client.perform(request))
+    }
+
+    /// Open an event listener on the websocket of the same node, see
+    /// `Client::event_listener`.
+    pub fn event_listener(&self) -> Result<BlockingEventListener, Error> {
+        Ok(BlockingEventListener {
+            listener: self.block_on(self.client.event_listener())?,
+            runtime: self.runtime.clone(),
+        })
+    }
+}
+
+/// Event listener with blocking methods, opened by
+/// `BlockingClient::event_listener`
+///
+/// Iterating over it yields events until the subscription is closed, e.g.
+/// because the websocket was closed. Errors, including the node dropping the
+/// subscription because events were not read fast enough, are yielded.
+pub struct BlockingEventListener {
+    /// Listener events are read from
+    listener: EventListener,
+
+    /// Runtime of the client the listener was opened by, which drives its
+    /// websocket
+    runtime: Arc<Runtime>,
+}
+
+impl BlockingEventListener {
+    /// Subscribe to events, see `EventListener::subscribe`.
+    pub fn subscribe(&mut self, query: EventSubscription) -> Result<(), Error> {
+        block_on(&self.runtime, self.listener.subscribe(query))
+    }
+
+    /// Wait for the next event for at most `timeout`, returning `None` if
+    /// none was received in time
+    ///
+    /// Frames other than events are skipped, and errors returned as by
+    /// `EventListener::get_event`.
+    pub fn next_event(&mut self, timeout: Duration) -> Result<Option<ResultEvent>, Error> {
+        let next = tokio::time::timeout(timeout, next_event(&mut self.listener));
+
+        match block_on(&self.runtime, next) {
+            Ok(result) => result.map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+impl Iterator for BlockingEventListener {
+    type Item = Result<ResultEvent, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match block_on(&self.runtime, next_event(&mut self.listener)) {
+            Err(e) if e.code() == Code::SubscriptionClosed => None,
+            result => Some(result),
+        }
+    }
+}
+
+/// Read events from `listener` until one is received, skipping other frames
+async fn next_event(listener: &mut EventListener) -> Result<ResultEvent, Error> {
+    loop {
+        if let Some(event) = listener.get_event().await? {
+            return Ok(event);
+        }
+    }
+}
+
+/// Run `future` to completion on `runtime`
+///
+/// # Panics
+///
+/// Panics if called from async code: blocking its thread would stall the
+/// tasks of its runtime, if not deadlock it.
+fn block_on<F: Future>(runtime: &Runtime, future: F) -> F::Output {
+    if Handle::try_current().is_ok() {
+        panic!(
+            "blocking RPC client called from async code, which should use \
+             `tendermint_rpc::Client` instead"
+        );
+    }
+
+    runtime.handle().block_on(future)
+}
@@ -0,0 +1,268 @@
+//! Builders for Tendermint RPC clients and event listeners, validating their
+//! options up front
+
+use http::HeaderValue;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use tendermint::chain;
+use tendermint::net;
+
+use super::event_listener::EventListener;
+use super::rate_limit::RateLimiter;
+use super::Client;
+use crate::{CompatMode, Error, IdProvider, RandomUuidProvider};
+
+/// Builder for a `Client`, returned by `Client::builder`
+///
+/// Each option defaults to what `Client::new` does, and unlike the `with_*`
+/// methods of `Client`, invalid options are reported by `build` rather than
+/// by the first request or by a panic.
+#[derive(Debug)]
+pub struct ClientBuilder {
+    /// Client the valid options are applied to
+    client: Client,
+
+    /// `User-Agent` sent with each request, until validated
+    user_agent: Option<String>,
+
+    /// Limit on the number of concurrent requests, until validated
+    max_in_flight_requests: Option<usize>,
+
+    /// Limit on the rate at which requests are sent, until validated
+    rate_limit: Option<u32>,
+}
+
+impl ClientBuilder {
+    /// Start building a client connecting to the given address
+    pub(super) fn new(address: net::Address) -> Self {
+        Self {
+            client: Client::new(address),
+            user_agent: None,
+            max_in_flight_requests: None,
+            rate_limit: None,
+        }
+    }
+
+    /// See `Client::with_user_agent`
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// See `Client::with_host_override`
+    pub fn host_override(mut self, host: impl Into<String>, ip: IpAddr) -> Self {
+        self.client = self.client.with_host_override(host, ip);
+        self
+    }
+
+    /// See `Client::with_id_provider`
+    pub fn id_provider(mut self, id_provider: impl IdProvider + 'static) -> Self {
+        self.client = self.client.with_id_provider(id_provider);
+        self
+    }
+
+    /// See `Client::with_lenient_responses`
+    pub fn lenient_responses(mut self) -> Self {
+        self.client = self.client.with_lenient_responses();
+        self
+    }
+
+    /// See `Client::with_compat_mode`
+    pub fn compat_mode(mut self, compat: CompatMode) -> Self {
+        self.client = self.client.with_compat_mode(compat);
+        self
+    }
+
+    /// See `Client::with_expected_chain_id`
+    pub fn expected_chain_id(mut self, chain_id: chain::Id) -> Self {
+        self.client = self.client.with_expected_chain_id(chain_id);
+        self
+    }
+
+    /// See `Client::with_max_in_flight_requests`
+    pub fn max_in_flight_requests(mut self, limit: usize) -> Self {
+        self.max_in_flight_requests = Some(limit);
+        self
+    }
+
+    /// See `Client::with_rate_limit`
+    pub fn rate_limit(mut self, requests_per_second: u32) -> Self {
+        self.rate_limit = Some(requests_per_second);
+        self
+    }
+
+    /// Build the client, or fail with a `Code::InvalidParams` error naming
+    /// the first invalid option
+    pub fn build(self) -> Result<Client, Error> {
+        let mut client = self.client;
+
+        check_address(&client.address)?;
+
+        if let Some(user_agent) = self.user_agent {
+            check_user_agent(&user_agent)?;
+            client = client.with_user_agent(user_agent);
+        }
+
+        if let Some(limit) = self.max_in_flight_requests {
+            if limit == 0 {
+                return Err(Error::invalid_params(
+                    "invalid limit on requests in flight: no request could ever be sent",
+                ));
+            }
+            client = client.with_max_in_flight_requests(limit);
+        }
+
+        if let Some(requests_per_second) = self.rate_limit {
+            check_rate_limit(requests_per_second)?;
+            client = client.with_rate_limit(requests_per_second);
+        }
+
+        Ok(client)
+    }
+}
+
+/// Builder for an `EventListener`, returned by `EventListener::builder`
+///
+/// Each option defaults to what `EventListener::connect` does. Invalid
+/// options are reported by `connect` before connecting.
+#[derive(Debug)]
+pub struct EventListenerBuilder {
+    /// Address of the node
+    address: net::Address,
+
+    /// `User-Agent` sent with the websocket handshake
+    user_agent: String,
+
+    /// IPs to connect to instead of resolving the given hosts
+    host_overrides: HashMap<String, IpAddr>,
+
+    /// Generates the ID of each request
+    id_provider: Arc<dyn IdProvider>,
+
+    /// Size from which frames are parsed on a blocking thread
+    large_frame_size: Option<usize>,
+
+    /// Tendermint version whose encodings events are decoded with
+    compat: CompatMode,
+
+    /// Limit on the rate at which subscribe requests are sent
+    rate_limit: Option<u32>,
+}
+
+impl EventListenerBuilder {
+    /// Start building an event listener connecting to the given address
+    pub(super) fn new(address: net::Address) -> Self {
+        Self {
+            address,
+            user_agent: super::USER_AGENT.to_owned(),
+            host_overrides: HashMap::new(),
+            id_provider: Arc::new(RandomUuidProvider),
+            large_frame_size: None,
+            compat: CompatMode::default(),
+            rate_limit: None,
+        }
+    }
+
+    /// See `EventListener::connect_with_user_agent`
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// See `Client::with_host_override`
+    pub fn host_override(mut self, host: impl Into<String>, ip: IpAddr) -> Self {
+        self.host_overrides.insert(host.into(), ip);
+        self
+    }
+
+    /// See `EventListener::with_id_provider`
+    pub fn id_provider(mut self, id_provider: impl IdProvider + 'static) -> Self {
+        self.id_provider = Arc::new(id_provider);
+        self
+    }
+
+    /// See `EventListener::with_large_frame_size`
+    pub fn large_frame_size(mut self, size: usize) -> Self {
+        self.large_frame_size = Some(size);
+        self
+    }
+
+    /// See `EventListener::with_compat_mode`
+    pub fn compat_mode(mut self, compat: CompatMode) -> Self {
+        self.compat = compat;
+        self
+    }
+
+    /// Limit the rate at which subscribe requests are sent, see
+    /// `Client::with_rate_limit`
+    pub fn rate_limit(mut self, requests_per_second: u32) -> Self {
+        self.rate_limit = Some(requests_per_second);
+        self
+    }
+
+    /// Connect the event listener, or fail with a `Code::InvalidParams` error
+    /// naming the first invalid option
+    pub async fn connect(self) -> Result<EventListener, Error> {
+        check_address(&self.address)?;
+        check_user_agent(&self.user_agent)?;
+        if let Some(requests_per_second) = self.rate_limit {
+            check_rate_limit(requests_per_second)?;
+        }
+
+        let (host, port) = match &self.address {
+            net::Address::Tcp { host, port, .. } => (host.as_str(), *port),
+            net::Address::Unix { .. } => unreachable!("checked above"),
+        };
+        let connect_to = self
+            .host_overrides
+            .get(host)
+            .map(|ip| SocketAddr::new(*ip, port));
+
+        let mut listener = EventListener::open(host, port, connect_to, &self.user_agent)
+            .await?
+            .with_rate_limit(
+                self.rate_limit
+                    .map(|requests_per_second| Arc::new(RateLimiter::new(requests_per_second))),
+            )
+            .with_compat_mode(self.compat);
+        listener.id_provider = self.id_provider;
+        if let Some(size) = self.large_frame_size {
+            listener = listener.with_large_frame_size(size);
+        }
+
+        Ok(listener)
+    }
+}
+
+/// Check that the node can be reached at `address`
+fn check_address(address: &net::Address) -> Result<(), Error> {
+    match address {
+        net::Address::Tcp { .. } => Ok(()),
+        net::Address::Unix { path } => Err(Error::invalid_params(&format!(
+            "invalid RPC address: {} is a UNIX socket, only TCP is supported",
+            path.display()
+        ))),
+    }
+}
+
+/// Check that `user_agent` can be sent as a header
+fn check_user_agent(user_agent: &str) -> Result<(), Error> {
+    HeaderValue::from_str(user_agent).map(|_| ()).map_err(|_| {
+        Error::invalid_params(&format!(
+            "invalid User-Agent: {:?} is not a valid header value",
+            user_agent
+        ))
+    })
+}
+
+/// Check that requests can be sent at the given rate
+fn check_rate_limit(requests_per_second: u32) -> Result<(), Error> {
+    if requests_per_second == 0 {
+        return Err(Error::invalid_params(
+            "invalid rate limit: no request could ever be sent",
+        ));
+    }
+    Ok(())
+}
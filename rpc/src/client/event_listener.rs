@@ -3,30 +3,49 @@
 // TODO(ismail): document fields or re-use the abci types
 #![allow(missing_docs)]
 
-use async_tungstenite::{tokio::connect_async, tokio::TokioAdapter, tungstenite::Message};
+use async_tungstenite::tokio::{client_async, connect_async, TokioAdapter};
+use async_tungstenite::tungstenite::protocol::CloseFrame;
+use async_tungstenite::tungstenite::Message;
+use bytes::Bytes;
 use futures::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::error::Error as stdError;
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::Arc;
 use tokio::net::TcpStream;
 
+use tendermint::abci;
 use tendermint::block;
 use tendermint::net;
+use tendermint::serializers;
+use tendermint::Time;
 
-use crate::error::Code;
+use super::rate_limit::RateLimiter;
+use super::{Client, EventListenerBuilder};
+use crate::endpoint::{subscribe, tx, tx_search};
+use crate::request::into_json_with_id;
 use crate::response;
-use crate::response::Wrapper;
-use crate::Request;
-use crate::{endpoint::subscribe, Error as RPCError};
+use crate::response::{WithRaw, Wrapper};
+use crate::{error::Code, CompatMode, Error as RPCError};
+use crate::{Id, IdProvider, Method, RandomUuidProvider};
 
-/// There are only two valid queries to the websocket. A query that subscribes to all transactions
-/// and a query that susbscribes to all blocks.
+/// Queries the websocket can be subscribed to: all transactions, all blocks,
+/// all block headers, or all events of any type.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum EventSubscription {
     /// Subscribe to all transactions
     TransactionSubscription,
     ///Subscribe to all blocks
     BlockSubscription,
+    /// Subscribe to all block headers
+    BlockHeaderSubscription,
+    /// Subscribe to all events of any type, e.g. for debugging or archival.
+    ///
+    /// This includes every transaction and a `NewBlock` event carrying the
+    /// full block at each height, which is a lot of data on a busy chain. The
+    /// node drops clients which do not keep up.
+    AllEventsSubscription,
 }
 
 impl EventSubscription {
@@ -35,6 +54,8 @@ impl EventSubscription {
         match self {
             EventSubscription::TransactionSubscription => "tm.event='Tx'",
             EventSubscription::BlockSubscription => "tm.event='NewBlock'",
+            EventSubscription::BlockHeaderSubscription => "tm.event='NewBlockHeader'",
+            EventSubscription::AllEventsSubscription => "tm.event EXISTS",
         }
     }
 }
@@ -43,11 +64,54 @@ impl EventSubscription {
 /// See: <https://docs.tendermint.com/master/rpc/#/Websocket/subscribe>
 pub struct EventListener {
     socket: async_tungstenite::WebSocketStream<TokioAdapter<TcpStream>>,
+
+    /// IDs of the subscribe requests accepted by the node, which errors
+    /// about the subscriptions are sent under
+    subscriptions: Vec<Id>,
+
+    /// The node's limit on subscriptions per client, once known
+    max_subscriptions: Option<usize>,
+
+    /// Size from which frames are parsed on a blocking thread
+    large_frame_size: Option<usize>,
+
+    /// Tendermint version whose encodings events are decoded with
+    compat: CompatMode,
+
+    /// Generates the ID of each request
+    pub(super) id_provider: Arc<dyn IdProvider>,
+
+    /// Limits the rate at which subscribe requests are sent, shared with
+    /// the client the listener was opened by
+    rate_limit: Option<Arc<RateLimiter>>,
+
+    /// Events missed before resumed subscriptions were made, delivered
+    /// before any further frame is read
+    backfill: VecDeque<ResultEvent>,
+
+    /// Marker of the last event backfilled for each resumed query, up to
+    /// which live events of that query are skipped as already delivered
+    resumed: HashMap<String, EventMarker>,
 }
 
 impl EventListener {
+    /// Start building an event listener connecting to the given address, to
+    /// set several options at once
+    pub fn builder(address: net::Address) -> EventListenerBuilder {
+        EventListenerBuilder::new(address)
+    }
+
     /// Constructor for event listener
     pub async fn connect(address: net::Address) -> Result<EventListener, RPCError> {
+        Self::connect_with_user_agent(address, super::USER_AGENT).await
+    }
+
+    /// Connect an event listener, identifying it to the node with the given
+    /// `User-Agent` instead of `tendermint-rpc/<version>`
+    pub async fn connect_with_user_agent(
+        address: net::Address,
+        user_agent: &str,
+    ) -> Result<EventListener, RPCError> {
         let (host, port) = match address {
             net::Address::Tcp { host, port, .. } => (host, port),
             other => {
@@ -57,19 +121,166 @@ impl EventListener {
                 )));
             }
         };
+        Self::open(&host, port, None, user_agent).await
+    }
+
+    /// Open the websocket of the node at `host` and `port`, connecting to
+    /// `connect_to` instead of resolving `host` if given
+    pub(crate) async fn open(
+        host: &str,
+        port: u16,
+        connect_to: Option<SocketAddr>,
+        user_agent: &str,
+    ) -> Result<EventListener, RPCError> {
+        let request = http::Request::builder()
+            .uri(format!("ws://{}:{}/websocket", host, port))
+            .header(http::header::USER_AGENT, user_agent)
+            .body(())?;
         //TODO This doesn't have any way to handle a connection over TLS
-        let (ws_stream, _unused_tls_stream) =
-            connect_async(&format!("ws://{}:{}/websocket", host, port)).await?;
-        Ok(EventListener { socket: ws_stream })
+        let (ws_stream, _unused_tls_stream) = match connect_to {
+            Some(address) => {
+                let stream = TcpStream::connect(address)
+                    .await
+                    .map_err(|e| RPCError::from_source(Code::WebSocketError, e))?;
+                client_async(request, stream).await?
+            }
+            None => connect_async(request).await?,
+        };
+        Ok(EventListener {
+            socket: ws_stream,
+            subscriptions: Vec::new(),
+            max_subscriptions: None,
+            large_frame_size: None,
+            compat: CompatMode::default(),
+            id_provider: Arc::new(RandomUuidProvider),
+            rate_limit: None,
+            backfill: VecDeque::new(),
+            resumed: HashMap::new(),
+        })
+    }
+
+    /// Parse frames of at least `size` bytes, such as `NewBlock` events for
+    /// large blocks, on a blocking thread.
+    ///
+    /// This keeps the async runtime free to run other tasks while the frame
+    /// is parsed. Events are still returned in the order they were received.
+    pub fn with_large_frame_size(mut self, size: usize) -> Self {
+        self.large_frame_size = Some(size);
+        self
+    }
+
+    /// Decode events with the encodings of the given Tendermint version,
+    /// instead of those of v0.34
+    pub fn with_compat_mode(mut self, compat: CompatMode) -> Self {
+        self.compat = compat;
+        self
+    }
+
+    /// Generate request IDs with the given provider, instead of using random
+    /// UUIDs
+    pub fn with_id_provider(mut self, id_provider: impl IdProvider + 'static) -> Self {
+        self.id_provider = Arc::new(id_provider);
+        self
+    }
+
+    /// Count subscribe requests against the given rate limit
+    pub(crate) fn with_rate_limit(mut self, rate_limit: Option<Arc<RateLimiter>>) -> Self {
+        self.rate_limit = rate_limit;
+        self
     }
 
     /// Subscribe to event query stream over the websocket
-    pub async fn subscribe(&mut self, query: EventSubscription) -> Result<(), Box<dyn stdError>> {
-        self.socket
-            .send(Message::text(
-                subscribe::Request::new(query.as_str().to_owned()).into_json(),
-            ))
-            .await?;
+    ///
+    /// Subscriptions made on a listener opened by `Client::event_listener`
+    /// count against the client's rate limit, if any.
+    pub async fn subscribe(&mut self, query: EventSubscription) -> Result<(), RPCError> {
+        self.subscribe_query(query.as_str()).await
+    }
+
+    /// Subscribe to `query`, first delivering the events missed since the
+    /// event marked by `resume_from`, if any, e.g. the last event seen before
+    /// a restart
+    ///
+    /// The subscription is made before the missed events are queried through
+    /// `client`, which must talk to the same node, so that no event falls
+    /// between the two. `get_event` then returns the missed events in order,
+    /// followed by live events, skipping those which were also backfilled.
+    ///
+    /// `BlockSubscription` resumes from an `EventMarker::Block`, whose
+    /// missed blocks are fetched with `/block`, and `TransactionSubscription`
+    /// from an `EventMarker::Tx`, whose missed transactions are found with
+    /// `/tx_search`. Other combinations fail with a `Code::InvalidParams`
+    /// error without subscribing. Backfilled `NewBlock` events carry no
+    /// `BeginBlock` or `EndBlock` results.
+    ///
+    /// If the missed events cannot be queried, the error is returned but the
+    /// subscription is kept, so live events are still delivered.
+    pub async fn subscribe_from(
+        &mut self,
+        client: &Client,
+        query: EventSubscription,
+        resume_from: Option<EventMarker>,
+    ) -> Result<(), RPCError> {
+        let marker = match resume_from {
+            Some(marker) => marker,
+            None => return self.subscribe(query).await,
+        };
+
+        match (&query, marker) {
+            (EventSubscription::BlockSubscription, EventMarker::Block(_))
+            | (EventSubscription::TransactionSubscription, EventMarker::Tx { .. }) => (),
+            _ => {
+                return Err(RPCError::invalid_params(&format!(
+                    "cannot resume {} from {:?}",
+                    query.as_str(),
+                    marker
+                )))
+            }
+        }
+
+        let query = query.as_str().to_owned();
+        self.subscribe_query(query.clone()).await?;
+
+        let missed = match marker {
+            EventMarker::Block(height) => missed_blocks(client, &query, height).await?,
+            EventMarker::Tx { height, index } => missed_txs(client, &query, height, index).await?,
+        };
+
+        let last = missed
+            .last()
+            .and_then(ResultEvent::marker)
+            .unwrap_or(marker);
+        self.resumed.insert(query, last);
+        self.backfill.extend(missed);
+        Ok(())
+    }
+
+    /// Subscribe to `NewBlock` events, received as
+    /// `TMEventData::EventDataNewBlock`
+    pub async fn subscribe_new_blocks(&mut self) -> Result<(), RPCError> {
+        self.subscribe(EventSubscription::BlockSubscription).await
+    }
+
+    /// Subscribe to `NewBlockHeader` events, received as
+    /// `TMEventData::EventDataNewBlockHeader`
+    ///
+    /// Much lighter than `subscribe_new_blocks` on chains with large blocks,
+    /// as the events omit the blocks' transactions, evidence and last commit.
+    pub async fn subscribe_new_block_headers(&mut self) -> Result<(), RPCError> {
+        self.subscribe(EventSubscription::BlockHeaderSubscription)
+            .await
+    }
+
+    /// Subscribe to the events matching an arbitrary query, e.g.
+    /// `tm.event='Tx' AND tx.hash='<hash>'` for a given transaction
+    pub async fn subscribe_query(&mut self, query: impl Into<String>) -> Result<(), RPCError> {
+        if let Some(rate_limit) = &self.rate_limit {
+            rate_limit.acquire().await;
+        }
+
+        let request_id = self.id_provider.next_id();
+        let request = into_json_with_id(subscribe::Request::new(query.into()), request_id.clone());
+        self.socket.send(Message::text(request)).await?;
         // TODO(ismail): this works if subscriptions are fired sequentially and no event or
         // ping message gets in the way:
         // Wait for an empty response on subscribe
@@ -77,39 +288,417 @@ impl EventListener {
             .socket
             .next()
             .await
-            .ok_or_else(|| RPCError::websocket_error("web socket closed"))??;
-        serde_json::from_str::<Wrapper<subscribe::Response>>(&msg.to_string())?.into_result()?;
+            .ok_or_else(|| RPCError::subscription_closed("web socket closed"))??;
+        if let Err(error) = subscribe_response(&request_id, &msg.into_data()) {
+            self.max_subscriptions = subscription_limit(&error).or(self.max_subscriptions);
+            return Err(error);
+        }
 
+        self.subscriptions.push(request_id);
         Ok(())
     }
 
+    /// Number of further subscriptions the node will accept on this
+    /// connection
+    ///
+    /// Tendermint only reports its per-client subscription limit when a
+    /// subscription is rejected for exceeding it, so this is `None` until
+    /// that happens.
+    pub fn remaining_server_subscriptions(&self) -> Option<usize> {
+        self.max_subscriptions
+            .map(|max| max.saturating_sub(self.subscriptions.len()))
+    }
+
     /// Get the next event from the websocket
+    ///
+    /// Each frame read from the websocket is classified as one of:
+    ///
+    /// - an event, returned as `Ok(Some(event))`
+    /// - a JSONRPC error about one of the listener's subscriptions, e.g. the
+    ///   node cancelling it, or an event of a known type which could not be
+    ///   parsed, returned as `Err`
+    /// - anything else, such as the response to a request, an error about
+    ///   another request, a notification from the node or a frame which is
+    ///   not JSONRPC, which is skipped and returned as `Ok(None)`
+    ///
+    /// None of these close the websocket, so events can still be read after
+    /// an error or a skipped frame. A close frame from the node is returned as
+    /// an error for which `is_subscription_closed` holds.
+    ///
+    /// Events backfilled by `subscribe_from` are returned before any further
+    /// frame is read.
     pub async fn get_event(&mut self) -> Result<Option<ResultEvent>, RPCError> {
+        Ok(self.get_event_with_raw().await?.map(|event| event.parsed))
+    }
+
+    /// Get the next event from the websocket, along with the frame it was
+    /// parsed from, see `get_event`
+    ///
+    /// Useful for keeping events exactly as the node sent them, e.g. to
+    /// record them as fixtures.
+    pub async fn get_event_with_raw(&mut self) -> Result<Option<WithRaw<ResultEvent>>, RPCError> {
+        if let Some(event) = self.backfill.pop_front() {
+            // No frame carried backfilled events, so they come with their
+            // serialization instead
+            let raw = Bytes::from(serde_json::to_vec(&event)?);
+            return Ok(Some(WithRaw { parsed: event, raw }));
+        }
+
         let msg = self
             .socket
             .next()
             .await
-            .ok_or_else(|| RPCError::websocket_error("web socket closed"))??;
+            .ok_or_else(|| RPCError::subscription_closed("web socket closed"))??;
+
+        if let Message::Close(frame) = &msg {
+            return Err(close_error(frame.as_ref()));
+        }
+
+        // Taken before parsing, so that parsing time shows up in any latency
+        // measured from it
+        let received_at = Time::now();
+
+        let raw = Bytes::from(msg.into_data());
+        let event = parse_frame(
+            raw.clone(),
+            received_at,
+            self.large_frame_size,
+            self.compat,
+            &self.subscriptions,
+        )
+        .await?;
+        let event = event.filter(|event| !self.already_backfilled(event));
+        Ok(event.map(|parsed| WithRaw { parsed, raw }))
+    }
+
+    /// Whether the live `event` was already delivered by backfilling its
+    /// subscription, forgetting about the backfill once live events have
+    /// caught up with it
+    fn already_backfilled(&mut self, event: &ResultEvent) -> bool {
+        match self.resumed.get(&event.query) {
+            Some(last) if event.marker().map_or(false, |marker| marker <= *last) => true,
+            Some(_) => {
+                self.resumed.remove(&event.query);
+                false
+            }
+            None => false,
+        }
+    }
+}
+
+/// `NewBlock` events of the blocks committed after `height`, up to the latest
+/// one
+async fn missed_blocks(
+    client: &Client,
+    query: &str,
+    height: block::Height,
+) -> Result<Vec<ResultEvent>, RPCError> {
+    let latest = client.status().await?.sync_info.latest_block_height;
+
+    let mut events = Vec::new();
+    let mut next = height.increment();
+    while next <= latest {
+        let block = client.block(next).await?.block;
+        events.push(ResultEvent {
+            query: query.to_owned(),
+            data: TMEventData::EventDataNewBlock(EventDataNewBlock {
+                block: Some(block),
+                result_begin_block: None,
+                result_end_block: None,
+            }),
+            events: None,
+            received_at: None,
+        });
+        next = next.increment();
+    }
+    Ok(events)
+}
+
+/// `Tx` events of the transactions committed after the one at `index` in
+/// the block at `height`, in order
+async fn missed_txs(
+    client: &Client,
+    query: &str,
+    height: block::Height,
+    index: u32,
+) -> Result<Vec<ResultEvent>, RPCError> {
+    let search = format!("tx.height >= {}", height);
+
+    let mut txs = Vec::new();
+    let mut seen = 0;
+    for page in 1.. {
+        let response = client
+            .tx_search(
+                search.as_str(),
+                false,
+                page,
+                tx_search::MAX_PER_PAGE,
+                tx_search::Order::Ascending,
+            )
+            .await?;
+
+        // Count what the node returned rather than what was asked for, in
+        // case it returns smaller pages
+        seen += response.txs.len();
+        let done = response.txs.is_empty() || seen >= response.total_count as usize;
+        txs.extend(response.txs);
+        if done {
+            break;
+        }
+    }
+
+    txs.retain(|tx| (tx.height, tx.index) > (height, index));
+    txs.sort_by_key(|tx| (tx.height, tx.index));
+    Ok(txs.into_iter().map(|tx| tx_event(query, tx)).collect())
+}
+
+/// Event the node would have sent subscribers about a committed transaction
+fn tx_event(query: &str, tx: tx::Response) -> ResultEvent {
+    let events = composite_tx_events(&tx.hash.to_string(), tx.height, &tx.tx_result.events);
+
+    // Transactions serialize to base64, as events carry them
+    let encoded_tx = match serde_json::to_value(&tx.tx) {
+        Ok(serde_json::Value::String(encoded)) => encoded,
+        other => unreachable!("transaction serialized as {:?}", other),
+    };
+
+    ResultEvent {
+        query: query.to_owned(),
+        data: TMEventData::EventDataTx(EventDataTx {
+            tx_result: TxResult {
+                height: tx.height,
+                index: tx.index,
+                tx: encoded_tx,
+                result: TxResultResult {
+                    log: tx.tx_result.log.to_string(),
+                    gas_wanted: tx.tx_result.gas_wanted.to_string(),
+                    gas_used: tx.tx_result.gas_used.to_string(),
+                    events: tx.tx_result.events,
+                },
+            },
+        }),
+        events: Some(events),
+        received_at: None,
+    }
+}
+
+/// Events map of a transaction event, as built by the node: the event type,
+/// the transaction's hash and height, and the values of the ABCI events'
+/// attributes under `<event type>.<attribute key>`
+pub(crate) fn composite_tx_events(
+    hash: &str,
+    height: block::Height,
+    events: &[abci::Event],
+) -> HashMap<String, Vec<String>> {
+    let mut composite = HashMap::new();
+    composite.insert("tm.event".to_owned(), vec!["Tx".to_owned()]);
+    composite.insert("tx.hash".to_owned(), vec![hash.to_owned()]);
+    composite.insert("tx.height".to_owned(), vec![height.to_string()]);
+
+    for event in events {
+        for attribute in &event.attributes {
+            composite
+                .entry(format!("{}.{}", event.type_str, attribute.key.as_ref()))
+                .or_insert_with(Vec::new)
+                .push(attribute.value.as_ref().to_owned());
+        }
+    }
+    composite
+}
+
+/// Check the node's response to a subscribe request
+///
+/// Rejections due to the node's per-client subscription limit are reported
+/// with `Code::SubscriptionLimitReached`.
+fn subscribe_response(request_id: &Id, data: &[u8]) -> Result<(), RPCError> {
+    let response: Wrapper<subscribe::Response> = serde_json::from_slice(data).map_err(|error| {
+        RPCError::response_parse(Method::Subscribe, request_id, error.into(), data)
+    })?;
+
+    response
+        .into_result()
+        .map(|_| ())
+        .map_err(|error| match subscription_limit(&error) {
+            Some(_) => error.reclassify(Code::SubscriptionLimitReached),
+            None => error,
+        })
+}
+
+/// Extract the per-client subscription limit from the error returned by a
+/// node rejecting a subscription, i.e. `max_subscriptions_per_client 5 reached`
+fn subscription_limit(error: &RPCError) -> Option<usize> {
+    error
+        .data()?
+        .strip_prefix("max_subscriptions_per_client ")?
+        .strip_suffix(" reached")?
+        .parse()
+        .ok()
+}
+
+/// Parse a websocket frame according to `compat`, on a blocking thread if it
+/// is at least `large_frame_size` bytes
+async fn parse_frame(
+    data: Bytes,
+    received_at: Time,
+    large_frame_size: Option<usize>,
+    compat: CompatMode,
+    subscriptions: &[Id],
+) -> Result<Option<ResultEvent>, RPCError> {
+    match large_frame_size {
+        Some(size) if data.len() >= size => {
+            let subscriptions = subscriptions.to_vec();
+            tokio::task::spawn_blocking(move || {
+                compat.scope(|| receive_frame(&data, received_at, &subscriptions))
+            })
+            .await
+            .map_err(|e| RPCError::websocket_error(e.to_string()))?
+        }
+        _ => compat.scope(|| receive_frame(&data, received_at, subscriptions)),
+    }
+}
+
+/// Classify a websocket frame, stamping any event with the time the frame was
+/// received
+fn receive_frame(
+    data: &[u8],
+    received_at: Time,
+    subscriptions: &[Id],
+) -> Result<Option<ResultEvent>, RPCError> {
+    Ok(
+        classify_frame(data, subscriptions)?.map(|event| ResultEvent {
+            received_at: Some(received_at),
+            ..event
+        }),
+    )
+}
+
+/// Classify a websocket frame as an event, a JSONRPC error about one of the
+/// given subscriptions, or neither
+fn classify_frame(data: &[u8], subscriptions: &[Id]) -> Result<Option<ResultEvent>, RPCError> {
+    // Deserialize straight from the frame's bytes, without going through
+    // an intermediate `String` or `serde_json::Value`
+    let parse_error = match serde_json::from_slice::<WrappedResultEvent>(data) {
+        Ok(frame) => {
+            // notifications, e.g. heartbeats, are not about any subscription
+            if frame.is_notification() {
+                return Ok(None);
+            }
+            let id = frame.id().clone();
+            // error frames carry no result, so we will bubble the error up
+            // here, unless it is about some other request
+            return match frame.into_result() {
+                Ok(event) => Ok(Some(event)),
+                Err(error) if is_subscription_id(&id, subscriptions) => {
+                    Err(subscription_error(error))
+                }
+                Err(error) => {
+                    log::debug!("skipping error for request {:?}: {}", id, error);
+                    Ok(None)
+                }
+            };
+        }
+        Err(error) => error,
+    };
+    // Checked before responses, which events would otherwise be taken for, as
+    // responses to subscribe requests carry no fields
+    if let Ok(frame) = serde_json::from_slice::<Wrapper<GenericResultEvent>>(data) {
+        let id = frame.id().clone();
+        if let Ok(event) = frame.into_result() {
+            let event_type = event.data["type"].as_str().unwrap_or_default();
+
+            if KNOWN_EVENT_TYPES.contains(&event_type) {
+                return Err(RPCError::response_parse(
+                    Method::Subscribe,
+                    &id,
+                    parse_error.into(),
+                    data,
+                ));
+            }
+
+            log::debug!("received event of unknown type {:?}", event_type);
+            return Ok(Some(ResultEvent {
+                query: event.query,
+                data: TMEventData::GenericJSONEvent(event.data),
+                events: event.events,
+                received_at: None,
+            }));
+        }
+    }
+    if serde_json::from_slice::<Wrapper<subscribe::Response>>(data).is_ok() {
+        // a response to a request rather than an event; errors were handled
+        // above
+        return Ok(None);
+    }
+    log::debug!(
+        "received neither event nor JSONRPC response, skipping: {}",
+        String::from_utf8_lossy(data)
+    );
+    Ok(None)
+}
+
+/// Types of the events `TMEventData` is parsed from, as sent by the node.
+/// Events of other types are parsed as `TMEventData::GenericJSONEvent`.
+const KNOWN_EVENT_TYPES: &[&str] = &[
+    "tendermint/event/NewBlock",
+    "EventDataNewBlock",
+    "tendermint/event/NewBlockHeader",
+    "EventDataNewBlockHeader",
+    "tendermint/event/Tx",
+    "EventDataTx",
+];
+
+/// Event whose data is kept as JSON, for events of unknown types
+#[derive(Serialize, Deserialize)]
+struct GenericResultEvent {
+    query: String,
+    data: serde_json::Value,
+    events: Option<HashMap<String, Vec<String>>>,
+}
+impl response::Response for GenericResultEvent {}
+
+/// Is `id` that of one of the given subscriptions, either as it is or, as
+/// Tendermint v0.33 sends it, followed by `#event`?
+fn is_subscription_id(id: &Id, subscriptions: &[Id]) -> bool {
+    subscriptions.iter().any(|subscription| {
+        let event_id = match subscription {
+            Id::Num(id) => format!("{}#event", id),
+            Id::Str(id) => format!("{}#event", id),
+            Id::None => return false,
+        };
+        id == subscription || *id == Id::Str(event_id)
+    })
+}
+
+/// Reason given by the node when it cancels the subscriptions of a client
+/// which does not read events fast enough
+const SLOW_CLIENT_REASON: &str = "client is not pulling messages fast enough";
+
+/// Report the node cancelling a subscription, e.g. because it is shutting
+/// down, as `Code::SubscriptionClosed`, or as `Code::ServerDroppedSlowClient`
+/// if the client was too slow
+fn subscription_error(error: RPCError) -> RPCError {
+    let data = error.data().unwrap_or_default();
+    let code = if !data.starts_with("subscription was cancelled") {
+        return error;
+    } else if data.contains(SLOW_CLIENT_REASON) {
+        Code::ServerDroppedSlowClient
+    } else {
+        Code::SubscriptionClosed
+    };
+    error.reclassify(code)
+}
 
-        if let Ok(result_event) = serde_json::from_str::<WrappedResultEvent>(&msg.to_string()) {
-            // if we get an rpc error here, we will bubble it up:
-            return Ok(Some(result_event.into_result()?));
+/// Report the node closing the websocket, distinguishing slow clients being
+/// dropped from other reasons
+fn close_error(frame: Option<&CloseFrame<'_>>) -> RPCError {
+    match frame {
+        Some(frame) if frame.reason.contains(SLOW_CLIENT_REASON) => {
+            RPCError::server_dropped_slow_client(frame.reason.to_string())
         }
-        dbg!("We did not receive a valid JSONRPC wrapped ResultEvent!");
-        if serde_json::from_str::<String>(&msg.to_string()).is_ok() {
-            // FIXME(ismail): Until this is a proper websocket client
-            // (or the endpoint moved to grpc in tendermint), we accept whatever was read here
-            // dbg! it out and return None below.
-            dbg!("Instead of JSONRPC wrapped ResultEvent, we got:");
-            dbg!(&msg.to_string());
-            return Ok(None);
+        Some(frame) => {
+            RPCError::subscription_closed(format!("web socket closed: {}", frame.reason))
         }
-        dbg!("received neither event nor generic string message:");
-        dbg!(&msg.to_string());
-        Err(RPCError::new(
-            Code::Other(-1),
-            Some("received neither event nor generic string message".to_string()),
-        ))
+        None => RPCError::subscription_closed("web socket closed"),
     }
 }
 
@@ -118,42 +707,238 @@ impl EventListener {
 // We leave it here for now because unsupported types are still
 // decodeable via fallthrough variants (GenericJSONEvent).
 /// The Event enum is typed events emitted by the Websockets.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+///
+/// Serializes to the same `type`/`value` representation the node sends, so
+/// recorded events can be parsed back.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 #[serde(tag = "type", content = "value")]
 #[allow(clippy::large_enum_variant)]
 pub enum TMEventData {
     /// EventDataNewBlock is returned upon subscribing to "tm.event='NewBlock'"
-    #[serde(alias = "tendermint/event/NewBlock")]
+    #[serde(rename = "tendermint/event/NewBlock", alias = "EventDataNewBlock")]
     EventDataNewBlock(EventDataNewBlock),
 
+    /// EventDataNewBlockHeader is returned upon subscribing to
+    /// "tm.event='NewBlockHeader'"
+    #[serde(
+        rename = "tendermint/event/NewBlockHeader",
+        alias = "EventDataNewBlockHeader"
+    )]
+    EventDataNewBlockHeader(EventDataNewBlockHeader),
+
     /// EventDataTx is returned upon subscribing to "tm.event='Tx'"
-    #[serde(alias = "tendermint/event/Tx")]
+    #[serde(rename = "tendermint/event/Tx", alias = "EventDataTx")]
     EventDataTx(EventDataTx),
 
-    /// Generic event containing json data
+    /// Event of a type not listed above, holding its data object exactly as
+    /// the node sent it, `type` included
+    #[serde(skip_deserializing)]
     GenericJSONEvent(
         /// generic event json data
         serde_json::Value,
     ),
 }
 
+/// Borrowed form of the typed `TMEventData` variants, for serialization
+#[derive(Serialize)]
+#[serde(tag = "type", content = "value")]
+enum TMEventDataRef<'a> {
+    #[serde(rename = "tendermint/event/NewBlock")]
+    EventDataNewBlock(&'a EventDataNewBlock),
+    #[serde(rename = "tendermint/event/NewBlockHeader")]
+    EventDataNewBlockHeader(&'a EventDataNewBlockHeader),
+    #[serde(rename = "tendermint/event/Tx")]
+    EventDataTx(&'a EventDataTx),
+}
+
+impl Serialize for TMEventData {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            TMEventData::EventDataNewBlock(data) => {
+                TMEventDataRef::EventDataNewBlock(data).serialize(serializer)
+            }
+            TMEventData::EventDataNewBlockHeader(data) => {
+                TMEventDataRef::EventDataNewBlockHeader(data).serialize(serializer)
+            }
+            TMEventData::EventDataTx(data) => {
+                TMEventDataRef::EventDataTx(data).serialize(serializer)
+            }
+            // Already holds the `type`/`value` object the node sent
+            TMEventData::GenericJSONEvent(data) => data.serialize(serializer),
+        }
+    }
+}
+
 /// Event data from a subscription
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ResultEvent {
     /// Query for this result
     pub query: String,
     /// Tendermint EventData
     pub data: TMEventData,
     /// Event type and event attributes map
+    ///
+    /// Maps composite keys of the form `<event type>.<attribute key>` (e.g.
+    /// `tx.hash` or `transfer.recipient`) to all values reported for that
+    /// key by the event. The values are always plain strings, regardless of
+    /// how the attributes in `data` are encoded.
+    ///
+    /// Populated by Tendermint v0.33 and v0.34 nodes. `None` if the node did
+    /// not include it.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub events: Option<HashMap<String, Vec<String>>>,
+    /// Local time at which the frame carrying this event was read from the
+    /// websocket
+    ///
+    /// `None` unless the event was received by an [`EventListener`]. This is
+    /// not part of the wire format, so it is never serialized.
+    #[serde(skip)]
+    pub received_at: Option<Time>,
 }
 impl response::Response for ResultEvent {}
 
+impl ResultEvent {
+    /// Height of the block this event is about, if known
+    ///
+    /// `None` for generic events, and for `NewBlock` events without a block.
+    pub fn height(&self) -> Option<block::Height> {
+        match &self.data {
+            TMEventData::EventDataNewBlock(data) => {
+                data.block.as_ref().map(|block| block.header.height)
+            }
+            TMEventData::EventDataNewBlockHeader(data) => Some(data.header.height),
+            TMEventData::EventDataTx(data) => Some(data.tx_result.height),
+            TMEventData::GenericJSONEvent(_) => None,
+        }
+    }
+
+    /// Marker of this event, from which its subscription can be resumed with
+    /// `EventListener::subscribe_from`
+    ///
+    /// `None` for events which cannot be resumed from: `NewBlock` events
+    /// without a block, and events other than `NewBlock` and `Tx` ones.
+    pub fn marker(&self) -> Option<EventMarker> {
+        match &self.data {
+            TMEventData::EventDataNewBlock(data) => data
+                .block
+                .as_ref()
+                .map(|block| EventMarker::Block(block.header.height)),
+            TMEventData::EventDataTx(data) => Some(EventMarker::Tx {
+                height: data.tx_result.height,
+                index: data.tx_result.index,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Compare events by their position on the chain, e.g. to sort events
+    /// collected while backfilling: `events.sort_by(ResultEvent::cmp_position)`
+    ///
+    /// See `EventPosition` for the order. Events at the same position compare
+    /// equal, so a stable sort keeps them in the order they were received.
+    pub fn cmp_position(&self, other: &Self) -> Ordering {
+        self.position().cmp(&other.position())
+    }
+
+    /// Position of this event on the chain
+    ///
+    /// The event index is unset, as a transaction event stands for all the
+    /// ABCI events of its transaction; see `abci_events` for their positions.
+    pub fn position(&self) -> EventPosition {
+        let tx_index = match &self.data {
+            TMEventData::EventDataTx(data) => Some(data.tx_result.index),
+            _ => None,
+        };
+        EventPosition {
+            height: self.height(),
+            tx_index,
+            event_index: None,
+        }
+    }
+
+    /// ABCI events emitted by the transaction of a transaction event, each
+    /// with its position on the chain, in the order they were emitted
+    ///
+    /// Empty for other events.
+    pub fn abci_events(&self) -> Vec<(EventPosition, &abci::Event)> {
+        let position = self.position();
+        match &self.data {
+            TMEventData::EventDataTx(data) => (0..)
+                .zip(&data.tx_result.result.events)
+                .map(|(index, event)| {
+                    let position = EventPosition {
+                        event_index: Some(index),
+                        ..position
+                    };
+                    (position, event)
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Position of an event on the chain: the height of its block, the index of
+/// its transaction within the block, and its index among the ABCI events of
+/// that transaction
+///
+/// Positions are ordered by height, then by transaction index, with block
+/// events before transaction events, then by event index, with whole
+/// transactions before their ABCI events. Events without a height come after
+/// all others.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct EventPosition {
+    /// Height of the block, if known
+    pub height: Option<block::Height>,
+    /// Index of the transaction within its block, for transaction events
+    pub tx_index: Option<u32>,
+    /// Index of the ABCI event among those of its transaction
+    pub event_index: Option<u32>,
+}
+
+impl Ord for EventPosition {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |position: &Self| {
+            (
+                position.height.is_none(),
+                position.height,
+                position.tx_index,
+                position.event_index,
+            )
+        };
+        key(self).cmp(&key(other))
+    }
+}
+
+impl PartialOrd for EventPosition {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Last event seen on a subscription, from which the subscription can be
+/// resumed with `EventListener::subscribe_from`, e.g. after a restart
+///
+/// Markers of the same kind are ordered by their position on the chain.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum EventMarker {
+    /// `NewBlock` event of the block at this height
+    Block(block::Height),
+
+    /// `Tx` event of the transaction at `index` in the block at `height`
+    Tx {
+        /// Height of the block the transaction was included in
+        height: block::Height,
+        /// Position of the transaction within its block
+        index: u32,
+    },
+}
+
 /// JSONRPC wrapped ResultEvent
 pub type WrappedResultEvent = Wrapper<ResultEvent>;
 
 /// TX value
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct EventDataTx {
     /// The actual TxResult
     #[serde(rename = "TxResult")]
@@ -161,40 +946,81 @@ pub struct EventDataTx {
 }
 
 /// Tx Result
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct TxResult {
-    pub height: String,
-    pub index: i64,
+    /// Height of the block the transaction was included in
+    pub height: block::Height,
+    /// Position of the transaction within its block, starting at 0
+    pub index: u32,
     pub tx: String,
     pub result: TxResultResult,
 }
 
+impl TxResult {
+    /// Where the transaction is on the chain: the height of its block and
+    /// its index within that block
+    pub fn tx_location(&self) -> (block::Height, u32) {
+        (self.height, self.index)
+    }
+}
+
 /// TX Results Results
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct TxResultResult {
     pub log: String,
     pub gas_wanted: String,
     pub gas_used: String,
-    pub events: Vec<TmEvent>,
+    pub events: Vec<abci::Event>,
 }
 impl response::Response for TxResultResult {}
 
 /// Tendermint ABCI Events
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[deprecated(note = "events are now decoded as `tendermint::abci::Event`")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct TmEvent {
     #[serde(rename = "type")]
     pub event_type: String,
+    #[allow(deprecated)]
     pub attributes: Vec<Attribute>,
 }
+
+#[allow(deprecated)]
+impl From<TmEvent> for abci::Event {
+    fn from(event: TmEvent) -> Self {
+        abci::Event {
+            type_str: event.event_type,
+            attributes: event.attributes.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
 /// Event Attributes
-#[derive(Serialize, Deserialize, Debug, Clone)]
+///
+/// Tendermint v0.33 base64-encodes attribute keys and values, whereas v0.34
+/// sends them as plain strings. They are decoded according to the listener's
+/// `CompatMode`.
+#[deprecated(note = "event attributes are now decoded as `tendermint::abci::tag::Tag`")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Attribute {
+    #[serde(deserialize_with = "serializers::parse_attribute_string")]
     pub key: String,
+    #[serde(deserialize_with = "serializers::parse_attribute_string")]
     pub value: String,
 }
 
+#[allow(deprecated)]
+impl From<Attribute> for abci::tag::Tag {
+    fn from(attribute: Attribute) -> Self {
+        // Parsing tag keys and values from strings is infallible
+        abci::tag::Tag::new(
+            attribute.key.parse().unwrap(),
+            attribute.value.parse().unwrap(),
+        )
+    }
+}
+
 ///Block Value
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct EventDataNewBlock {
     pub block: Option<block::Block>,
 
@@ -204,13 +1030,275 @@ pub struct EventDataNewBlock {
     pub result_end_block: Option<ResultEndBlock>,
 }
 
+/// Block header value
+///
+/// A lighter alternative to [`EventDataNewBlock`] which omits the block's
+/// transactions, evidence and last commit.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventDataNewBlockHeader {
+    pub header: block::Header,
+
+    /// Number of transactions in the block
+    #[serde(with = "serializers::from_str")]
+    pub num_txs: u64,
+
+    pub result_begin_block: Option<ResultBeginBlock>,
+    pub result_end_block: Option<ResultEndBlock>,
+}
+
 /// Begin Block Events
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ResultBeginBlock {
-    pub events: Option<Vec<TmEvent>>,
+    pub events: Option<Vec<abci::Event>>,
 }
 ///End Block Events
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ResultEndBlock {
     pub validator_updates: Option<Vec<Option<serde_json::Value>>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::read_json_fixture;
+    use async_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+
+    const FIXTURES: &str = "./tests/support";
+
+    /// Subscription the fixtures' events and errors are about
+    const SUBSCRIPTIONS: &[Id] = &[Id::Num(0)];
+
+    #[test]
+    fn classify_event_frames() {
+        let event = classify_frame(
+            read_json_fixture(FIXTURES, "event_tx_v0_34").as_bytes(),
+            SUBSCRIPTIONS,
+        )
+        .unwrap();
+        assert!(matches!(event.unwrap().data, TMEventData::EventDataTx(_)));
+
+        let error = classify_frame(
+            read_json_fixture(FIXTURES, "event_error").as_bytes(),
+            SUBSCRIPTIONS,
+        )
+        .unwrap_err();
+        assert!(error.is_subscription_closed());
+        assert_eq!(
+            error.data(),
+            Some("subscription was cancelled (reason: Tendermint exited)")
+        );
+
+        // Errors are only about the subscriptions when sent under their IDs,
+        // as they are or followed by `#event`
+        let error = read_json_fixture(FIXTURES, "event_error").replace("0#event", "0");
+        assert!(classify_frame(error.as_bytes(), SUBSCRIPTIONS).is_err());
+        let error = read_json_fixture(FIXTURES, "event_error");
+        assert!(classify_frame(error.as_bytes(), &[Id::Num(1)])
+            .unwrap()
+            .is_none());
+        assert!(classify_frame(error.as_bytes(), &[]).unwrap().is_none());
+
+        // Events of known types which cannot be parsed are reported
+        let error = classify_frame(
+            read_json_fixture(FIXTURES, "event_malformed").as_bytes(),
+            SUBSCRIPTIONS,
+        )
+        .unwrap_err();
+        assert_eq!(error.code(), Code::ParseError);
+        assert_eq!(error.method(), Some(Method::Subscribe));
+
+        // Frames which are neither events nor errors are skipped
+        assert!(
+            classify_frame(br#"{"jsonrpc":"2.0","id":"1","result":{}}"#, SUBSCRIPTIONS)
+                .unwrap()
+                .is_none()
+        );
+        assert!(classify_frame(b"not json", SUBSCRIPTIONS)
+            .unwrap()
+            .is_none());
+
+        // So are notifications, with or without a null ID
+        assert!(classify_frame(
+            br#"{"jsonrpc":"2.0","id":null,"method":"heartbeat"}"#,
+            SUBSCRIPTIONS
+        )
+        .unwrap()
+        .is_none());
+        assert!(classify_frame(
+            br#"{"jsonrpc":"2.0","method":"heartbeat","params":{}}"#,
+            SUBSCRIPTIONS
+        )
+        .unwrap()
+        .is_none());
+
+        // Later events are still delivered
+        let event = classify_frame(
+            read_json_fixture(FIXTURES, "event_new_block").as_bytes(),
+            SUBSCRIPTIONS,
+        )
+        .unwrap();
+        assert!(matches!(
+            event.unwrap().data,
+            TMEventData::EventDataNewBlock(_)
+        ));
+    }
+
+    #[test]
+    fn unknown_event_type() {
+        let frame = read_json_fixture(FIXTURES, "event_unknown_type");
+        let event = classify_frame(frame.as_bytes(), SUBSCRIPTIONS)
+            .unwrap()
+            .expect("events of unknown types are passed on");
+
+        // The event serializes back to what the node sent
+        let original: serde_json::Value = serde_json::from_str(&frame).unwrap();
+        assert_eq!(serde_json::to_value(&event).unwrap(), original["result"]);
+
+        assert_eq!(event.query, "tm.event='ValidatorSetUpdates'");
+        assert_eq!(event.height(), None);
+        match event.data {
+            TMEventData::GenericJSONEvent(data) => {
+                assert_eq!(data["type"], "tendermint/event/ValidatorSetUpdates");
+                assert_eq!(data["value"]["validator_updates"][0]["voting_power"], "10");
+            }
+            other => panic!("expected a generic event, got {:?}", other),
+        }
+        assert_eq!(
+            event.events.unwrap()["tm.event"],
+            vec!["ValidatorSetUpdates".to_owned()]
+        );
+    }
+
+    #[test]
+    fn slow_client_dropped() {
+        let error = classify_frame(
+            read_json_fixture(FIXTURES, "event_error_slow_client").as_bytes(),
+            SUBSCRIPTIONS,
+        )
+        .unwrap_err();
+        assert_eq!(error.code(), Code::ServerDroppedSlowClient);
+        assert!(error.is_subscription_closed());
+
+        // The node may also close the websocket outright
+        let frame = CloseFrame {
+            code: CloseCode::Policy,
+            reason: SLOW_CLIENT_REASON.into(),
+        };
+        assert_eq!(
+            close_error(Some(&frame)).code(),
+            Code::ServerDroppedSlowClient
+        );
+
+        let frame = CloseFrame {
+            code: CloseCode::Away,
+            reason: "shutting down".into(),
+        };
+        assert_eq!(close_error(Some(&frame)).code(), Code::SubscriptionClosed);
+        assert_eq!(close_error(None).code(), Code::SubscriptionClosed);
+    }
+
+    #[test]
+    fn subscription_limit_reached() {
+        assert!(subscribe_response(
+            &Id::Str("1".to_owned()),
+            br#"{"jsonrpc":"2.0","id":"1","result":{}}"#
+        )
+        .is_ok());
+
+        let error = subscribe_response(
+            &Id::Str("1".to_owned()),
+            br#"{"jsonrpc":"2.0","id":"1","error":{"code":-32603,"message":"Internal error","data":"max_subscriptions_per_client 5 reached"}}"#,
+        )
+        .unwrap_err();
+        assert_eq!(error.code(), Code::SubscriptionLimitReached);
+        assert_eq!(error.jsonrpc_code(), Some(Code::InternalError));
+        assert_eq!(subscription_limit(&error), Some(5));
+
+        // Other rejections are passed through as they are
+        let error = subscribe_response(
+            &Id::Str("1".to_owned()),
+            br#"{"jsonrpc":"2.0","id":"1","error":{"code":-32603,"message":"Internal error","data":"failed to parse query"}}"#,
+        )
+        .unwrap_err();
+        assert_eq!(error.code(), Code::InternalError);
+        assert_eq!(subscription_limit(&error), None);
+    }
+
+    #[test]
+    fn receive_timestamps() {
+        let frame = read_json_fixture(FIXTURES, "event_tx_v0_34").into_bytes();
+
+        let first = receive_frame(&frame, Time::now(), SUBSCRIPTIONS)
+            .unwrap()
+            .unwrap();
+        let second = receive_frame(&frame, Time::now(), SUBSCRIPTIONS)
+            .unwrap()
+            .unwrap();
+        assert!(first.received_at.unwrap() <= second.received_at.unwrap());
+
+        // Events parsed outside of the listener carry no timestamp
+        let event = classify_frame(&frame, SUBSCRIPTIONS).unwrap().unwrap();
+        assert!(event.received_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn large_frames_keep_their_order() {
+        let frames = ["event_new_block", "event_tx_v0_34", "event_new_block"]
+            .iter()
+            .map(|name| read_json_fixture(FIXTURES, name).into_bytes())
+            .collect::<Vec<_>>();
+
+        // Only the NewBlock frames are large enough to be parsed on a
+        // blocking thread
+        let large_frame_size = frames[1].len() + 1;
+        assert!(frames[0].len() >= large_frame_size);
+
+        let mut events = Vec::new();
+        for frame in frames {
+            let event = parse_frame(
+                frame.into(),
+                Time::now(),
+                Some(large_frame_size),
+                CompatMode::V0_34,
+                SUBSCRIPTIONS,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+            events.push(event);
+        }
+
+        assert!(matches!(events[0].data, TMEventData::EventDataNewBlock(_)));
+        assert!(matches!(events[1].data, TMEventData::EventDataTx(_)));
+        assert!(matches!(events[2].data, TMEventData::EventDataNewBlock(_)));
+        for pair in events.windows(2) {
+            assert!(pair[0].received_at <= pair[1].received_at);
+        }
+    }
+
+    #[tokio::test]
+    async fn compat_mode_applies_on_blocking_threads() {
+        let frame = read_json_fixture(FIXTURES, "event_tx_v0_33").into_bytes();
+
+        for large_frame_size in &[None, Some(0)] {
+            let event = parse_frame(
+                frame.clone().into(),
+                Time::now(),
+                *large_frame_size,
+                CompatMode::V0_33,
+                SUBSCRIPTIONS,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+            let tx = match event.data {
+                TMEventData::EventDataTx(tx) => tx,
+                other => panic!("expected a Tx event, got {:?}", other),
+            };
+            let attribute = &tx.tx_result.result.events[0].attributes[0];
+            assert_eq!(attribute.key.as_ref(), "creator");
+            assert_eq!(attribute.value.as_ref(), "Cosmoshi Netowoko");
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Per-method request latency metrics
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::Method;
+
+/// Latency of the requests made with one method
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Latency {
+    /// Number of requests
+    pub count: u64,
+
+    /// Shortest request
+    pub min: Duration,
+
+    /// Longest request
+    pub max: Duration,
+
+    /// Time taken by all requests together
+    pub total: Duration,
+}
+
+impl Latency {
+    /// Latency of a single request
+    fn new(duration: Duration) -> Self {
+        Self {
+            count: 1,
+            min: duration,
+            max: duration,
+            total: duration,
+        }
+    }
+
+    /// Average time taken by a request
+    pub fn average(&self) -> Duration {
+        Duration::from_nanos((self.total.as_nanos() / u128::from(self.count)) as u64)
+    }
+
+    /// Take another request into account
+    fn record(&mut self, duration: Duration) {
+        self.count += 1;
+        self.min = self.min.min(duration);
+        self.max = self.max.max(duration);
+        self.total += duration;
+    }
+}
+
+/// Latency of the requests made by a client, by method
+#[derive(Debug, Default)]
+pub(crate) struct Metrics {
+    latencies: Mutex<HashMap<Method, Latency>>,
+}
+
+impl Metrics {
+    /// Record that a request with the given method took `duration`
+    pub(crate) fn record(&self, method: Method, duration: Duration) {
+        self.latencies
+            .lock()
+            .unwrap()
+            .entry(method)
+            .and_modify(|latency| latency.record(duration))
+            .or_insert_with(|| Latency::new(duration));
+    }
+
+    /// Copy of the latencies recorded so far
+    pub(crate) fn snapshot(&self) -> HashMap<Method, Latency> {
+        self.latencies.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,55 @@
+//! Client-side rate limiting of outgoing requests
+
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Token bucket limiting the rate at which requests are sent.
+///
+/// The bucket holds up to one second's worth of tokens, so short bursts up to
+/// the configured rate go out immediately. Once it is empty, callers are
+/// queued and released in order, one every `1 / rate` seconds.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    /// Time it takes for one token to be added to the bucket
+    interval: Duration,
+
+    /// How far ahead of schedule a request may be sent (the bucket size)
+    burst: Duration,
+
+    /// Time at which the next request is due if requests were sent at
+    /// exactly the configured rate
+    next_due: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    /// Create a rate limiter allowing `requests_per_second` requests per second
+    pub(crate) fn new(requests_per_second: u32) -> Self {
+        assert!(
+            requests_per_second > 0,
+            "rate limit must be at least one request per second"
+        );
+
+        let interval = Duration::from_secs(1) / requests_per_second;
+
+        Self {
+            interval,
+            burst: interval * (requests_per_second - 1),
+            next_due: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Take a token from the bucket, waiting for one to become available if
+    /// the bucket is empty.
+    pub(crate) async fn acquire(&self) {
+        let send_at = {
+            let mut next_due = self.next_due.lock().await;
+            let now = Instant::now();
+            let due = std::cmp::max(*next_due, now);
+            *next_due = due + self.interval;
+
+            due.checked_sub(self.burst).unwrap_or(now)
+        };
+
+        tokio::time::delay_until(send_at.into()).await;
+    }
+}
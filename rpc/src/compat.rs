@@ -0,0 +1,44 @@
+//! Compatibility with the encodings of different Tendermint versions
+
+use tendermint::serializers::{with_attribute_encoding, AttributeEncoding};
+
+/// Tendermint version whose encodings responses and events are decoded with
+///
+/// Tendermint v0.33 base64-encodes ABCI event attribute keys and values,
+/// whereas v0.34 sends them as plain strings. Neither can be told apart from
+/// the value alone, so the version of the node has to be known.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CompatMode {
+    /// Tendermint v0.33
+    V0_33,
+
+    /// Tendermint v0.34
+    V0_34,
+
+    /// Either version, e.g. while a network is being upgraded, guessing the
+    /// encoding of each attribute.
+    ///
+    /// Lossy: v0.34 attributes which happen to be valid base64 of UTF-8
+    /// text, such as `"Zm9v"`, are decoded anyway. Prefer one of the other
+    /// modes whenever the node's version is known.
+    Guess,
+}
+
+impl CompatMode {
+    /// Run `f`, decoding responses and events parsed by it on the current
+    /// thread according to this mode
+    pub fn scope<R>(self, f: impl FnOnce() -> R) -> R {
+        let encoding = match self {
+            CompatMode::V0_33 => AttributeEncoding::Base64,
+            CompatMode::V0_34 => AttributeEncoding::Plain,
+            CompatMode::Guess => AttributeEncoding::Guess,
+        };
+        with_attribute_encoding(encoding, f)
+    }
+}
+
+impl Default for CompatMode {
+    fn default() -> Self {
+        CompatMode::V0_34
+    }
+}
@@ -85,6 +85,9 @@ pub struct AbciQuery {
     pub value: Vec<u8>,
 
     /// Proof (might be explicit null)
+    ///
+    /// Tendermint v0.34 renamed this field to `proofOps`; both are accepted.
+    #[serde(alias = "proofOps")]
     pub proof: Option<Proof>,
 
     /// Block height
@@ -3,3 +3,29 @@
 pub mod tx_async;
 pub mod tx_commit;
 pub mod tx_sync;
+
+use tendermint::abci::Transaction;
+use tendermint::consensus;
+
+use crate::Error;
+
+/// Check that `tx` is not too large to ever be included in a block under the
+/// given consensus parameters, so that it can be rejected before it is
+/// broadcast
+///
+/// The parameters can be fetched once, e.g. from the genesis file, and
+/// reused for every transaction. Passing this check does not guarantee that
+/// the node accepts the transaction: blocks also carry headers, evidence and
+/// commits, and nodes may limit the size of transactions in their mempool
+/// further. Transactions failing it are rejected with `Code::TxTooLarge`, of
+/// the same `Kind::TxTooLarge` as the node's own rejection.
+pub fn check_tx_size(tx: &Transaction, params: &consensus::Params) -> Result<(), Error> {
+    let size = tx.as_bytes().len() as u64;
+    let max = params.block.max_bytes;
+
+    if size > max {
+        Err(Error::tx_too_large(size, max))
+    } else {
+        Ok(())
+    }
+}
@@ -1,8 +1,9 @@
 //! `/commit` endpoint JSONRPC wrapper
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
-use tendermint::{block, block::signed_header::SignedHeader};
+use tendermint::{block, block::signed_header::SignedHeader, validator};
 
 /// Get commit information about a specific block
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
@@ -38,3 +39,48 @@ pub struct Response {
 }
 
 impl crate::Response for Response {}
+
+impl Response {
+    /// Tally the voting power of the validators in the given set which signed
+    /// this commit, i.e. voted for its block ID.
+    ///
+    /// Absent signatures, votes for nil, signatures from validators outside
+    /// of the set and repeated signatures from the same validator do not
+    /// count towards the signed voting power. Signatures are not verified.
+    pub fn voting_power(&self, validators: &validator::Set) -> VotingPower {
+        let mut seen = HashSet::new();
+        let signed = self
+            .signed_header
+            .commit
+            .signatures
+            .iter()
+            .filter(|sig| sig.is_commit())
+            .filter_map(|sig| sig.validator_address())
+            .filter(|address| seen.insert(*address))
+            .filter_map(|address| validators.validator(address))
+            .map(|validator| validator.power())
+            .sum();
+
+        VotingPower {
+            signed,
+            total: validators.total_power(),
+        }
+    }
+}
+
+/// Voting power which signed a commit, relative to its validator set
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct VotingPower {
+    /// Voting power of the validators which signed the commit
+    pub signed: u64,
+
+    /// Total voting power of the validator set
+    pub total: u64,
+}
+
+impl VotingPower {
+    /// Did validators with more than 2/3 of the total voting power sign?
+    pub fn has_two_thirds_majority(&self) -> bool {
+        u128::from(self.signed) * 3 > u128::from(self.total) * 2
+    }
+}
@@ -0,0 +1,64 @@
+//! `/tx` endpoint JSONRPC wrapper
+
+use serde::{Deserialize, Serialize};
+
+use tendermint::abci::transaction::{self, TxProof};
+use tendermint::abci::{DeliverTx, Transaction};
+use tendermint::{block, serializers};
+
+/// Get a committed transaction by its hash
+///
+/// Requires the node to index transactions.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Request {
+    /// Hash of the transaction, sent base64-encoded
+    #[serde(with = "serializers::bytes::base64string")]
+    hash: Vec<u8>,
+
+    /// Include a proof of the transaction's inclusion in its block
+    prove: bool,
+}
+
+impl Request {
+    /// Get the committed transaction with the given hash, along with a
+    /// proof of its inclusion if `prove` is set
+    pub fn new(hash: transaction::Hash, prove: bool) -> Self {
+        Self {
+            hash: hash.as_bytes().to_vec(),
+            prove,
+        }
+    }
+}
+
+impl crate::Request for Request {
+    type Response = Response;
+
+    fn method(&self) -> crate::Method {
+        crate::Method::Tx
+    }
+}
+
+/// Committed transaction
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Response {
+    /// Transaction hash
+    pub hash: transaction::Hash,
+
+    /// Height of the block the transaction was committed in
+    pub height: block::Height,
+
+    /// Position of the transaction in its block
+    pub index: u32,
+
+    /// Result of executing the transaction
+    pub tx_result: DeliverTx,
+
+    /// The transaction itself
+    pub tx: Transaction,
+
+    /// Proof of the transaction's inclusion in its block, if requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof: Option<TxProof>,
+}
+
+impl crate::Response for Response {}
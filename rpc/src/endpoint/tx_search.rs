@@ -0,0 +1,109 @@
+//! `/tx_search` endpoint JSONRPC wrapper
+
+use serde::{Deserialize, Serialize};
+
+use super::tx;
+use crate::Error;
+use tendermint::serializers;
+
+/// Most results per page Tendermint returns; nodes silently clamp larger
+/// `per_page` values to it
+pub const MAX_PER_PAGE: u32 = 100;
+
+/// Search for committed transactions matching a query, e.g.
+/// `tx.height >= 5 AND transfer.recipient = 'cosmos1...'`
+///
+/// Requires the node to index transactions.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Request {
+    /// Query the transactions' events must match
+    query: String,
+
+    /// Include a proof of each transaction's inclusion in its block
+    prove: bool,
+
+    /// Page of results to get, starting at 1
+    #[serde(with = "serializers::from_str")]
+    page: u32,
+
+    /// Number of results per page
+    #[serde(with = "serializers::from_str")]
+    per_page: u32,
+
+    /// Order of the results by height
+    order_by: Order,
+}
+
+impl Request {
+    /// Search for the transactions matching `query`, getting page `page`
+    /// (starting at 1) of `per_page` results, along with proofs of their
+    /// inclusion if `prove` is set
+    ///
+    /// Nodes clamp `per_page` to `MAX_PER_PAGE`, see `check_per_page`.
+    pub fn new(
+        query: impl Into<String>,
+        prove: bool,
+        page: u32,
+        per_page: u32,
+        order_by: Order,
+    ) -> Self {
+        Self {
+            query: query.into(),
+            prove,
+            page,
+            per_page,
+            order_by,
+        }
+    }
+}
+
+/// Check that `per_page` is at most `MAX_PER_PAGE`
+///
+/// Nodes return pages of at most `MAX_PER_PAGE` results whatever the request
+/// asks for, so callers counting on larger pages would miss results without
+/// noticing. `Client::tx_search` rejects such requests with
+/// `Code::InvalidParams` before sending them.
+pub fn check_per_page(per_page: u32) -> Result<(), Error> {
+    if per_page > MAX_PER_PAGE {
+        Err(Error::invalid_params(&format!(
+            "per_page {} exceeds the maximum of {}",
+            per_page, MAX_PER_PAGE
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+impl crate::Request for Request {
+    type Response = Response;
+
+    fn method(&self) -> crate::Method {
+        crate::Method::TxSearch
+    }
+}
+
+/// Order of search results by height
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Order {
+    /// Lowest height first
+    #[serde(rename = "asc")]
+    Ascending,
+
+    /// Highest height first
+    #[serde(rename = "desc")]
+    Descending,
+}
+
+/// Page of transactions matching a search
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Response {
+    /// Matching transactions on this page, in the same form as `/tx`
+    /// returns them
+    pub txs: Vec<tx::Response>,
+
+    /// Number of matching transactions across all pages
+    #[serde(with = "serializers::from_str")]
+    pub total_count: u32,
+}
+
+impl crate::Response for Response {}
@@ -3,12 +3,21 @@
 #[cfg(feature = "client")]
 use async_tungstenite::tungstenite::Error as WSError;
 
+use crate::{Id, Method};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::{self, Display};
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 
+/// Maximum number of bytes of a response body included in parse errors
+const BODY_SNIPPET_LENGTH: usize = 256;
+
+/// Underlying cause of an error raised by the client
+type Source = Arc<dyn std::error::Error + Send + Sync + 'static>;
+
 /// Tendermint RPC errors
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Error {
     /// Error code
     code: Code,
@@ -16,10 +25,58 @@ pub struct Error {
     /// Error message
     message: String,
 
-    /// Additional data about the error
+    /// Additional data about the error (not always sent by the node)
+    #[serde(default)]
     data: Option<String>,
+
+    /// Underlying error, e.g. from the HTTP client or JSON parser. Never sent
+    /// over the wire.
+    #[serde(skip)]
+    source: Option<Source>,
+
+    /// How long the node asked us to wait before retrying
+    #[serde(skip)]
+    retry_after: Option<Duration>,
+
+    /// Request whose response could not be parsed
+    #[serde(skip)]
+    response: Option<Arc<ResponseContext>>,
+
+    /// Code of the JSONRPC error object this error was received as, if it
+    /// was sent by the node rather than raised by the client
+    #[serde(skip)]
+    jsonrpc_code: Option<Code>,
+}
+
+/// Context of a response which could not be parsed
+#[derive(Debug)]
+struct ResponseContext {
+    /// Method of the request
+    method: Method,
+
+    /// ID of the request
+    request_id: Id,
+
+    /// Start of the response body, at most `BODY_SNIPPET_LENGTH` bytes of it
+    body_snippet: String,
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| source.as_ref() as &(dyn std::error::Error + 'static))
+    }
+}
+
+// Errors are compared by what would be sent over the wire, ignoring the source
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        self.code == other.code && self.message == other.message && self.data == other.data
+    }
 }
-impl std::error::Error for Error {}
+
+impl Eq for Error {}
 
 impl Error {
     /// Create a new RPC error
@@ -30,6 +87,24 @@ impl Error {
             code,
             message,
             data,
+            source: None,
+            retry_after: None,
+            response: None,
+            jsonrpc_code: None,
+        }
+    }
+
+    /// Create a new error caused by `source`
+    ///
+    /// The source is available through `std::error::Error::source` rather
+    /// than being included in this error's message.
+    pub fn from_source<E>(code: Code, source: E) -> Error
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Error {
+            source: Some(Arc::new(source)),
+            ..Error::new(code, None)
         }
     }
 
@@ -39,6 +114,10 @@ impl Error {
             code: Code::HttpError,
             message: message.into(),
             data: None,
+            source: None,
+            retry_after: None,
+            response: None,
+            jsonrpc_code: None,
         }
     }
 
@@ -47,11 +126,57 @@ impl Error {
         Error::new(Code::InvalidParams, Some(data.to_string()))
     }
 
+    /// Create a new error for a transaction which the client found to be
+    /// larger than the chain allows, without sending it to the node
+    pub fn tx_too_large(size: u64, max: u64) -> Error {
+        Error::new(
+            Code::TxTooLarge,
+            Some(format!("tx size is too big: {}, max: {}", size, max)),
+        )
+    }
+
     /// Create a new websocket error
     pub fn websocket_error(cause: impl Into<String>) -> Error {
         Error::new(Code::WebSocketError, Some(cause.into()))
     }
 
+    /// Create a new timeout error
+    pub fn timeout(cause: impl Into<String>) -> Error {
+        Error::new(Code::Timeout, Some(cause.into()))
+    }
+
+    /// Create a new error for a subscription which has been closed, either by
+    /// the node or because the websocket was closed
+    pub fn subscription_closed(cause: impl Into<String>) -> Error {
+        Error::new(Code::SubscriptionClosed, Some(cause.into()))
+    }
+
+    /// Create a new error for a subscription which the node cancelled because
+    /// the client was not reading events fast enough
+    pub fn server_dropped_slow_client(cause: impl Into<String>) -> Error {
+        Error::new(Code::ServerDroppedSlowClient, Some(cause.into()))
+    }
+
+    /// Create a new error for a node on another chain than the expected one
+    pub fn chain_id_mismatch(expected: impl Display, actual: impl Display) -> Error {
+        Error::new(
+            Code::ChainIdMismatch,
+            Some(format!(
+                "expected chain ID {}, node is on {}",
+                expected, actual
+            )),
+        )
+    }
+
+    /// Create a new error for a node which is overloaded or rate limiting
+    /// requests, i.e. responded with HTTP status 429 or 503
+    pub fn server_busy(status: impl Display, retry_after: Option<Duration>) -> Error {
+        Error {
+            retry_after,
+            ..Error::new(Code::ServerBusy, Some(format!("HTTP status {}", status)))
+        }
+    }
+
     /// Create a new method-not-found error
     pub fn method_not_found(name: &str) -> Error {
         Error::new(Code::MethodNotFound, Some(name.to_string()))
@@ -65,6 +190,50 @@ impl Error {
         Error::new(Code::ParseError, Some(error.to_string()))
     }
 
+    /// Create a new error for a response which is not a valid JSONRPC 2.0
+    /// response, naming the rule it violates
+    pub fn invalid_envelope(violation: impl Display) -> Error {
+        Error::new(
+            Code::ParseError,
+            Some(format!("invalid JSONRPC envelope: {}", violation)),
+        )
+    }
+
+    /// Create a new error for a response to the request with the given
+    /// method and ID which could not be parsed
+    ///
+    /// The start of the response body is included to help diagnose version
+    /// mismatches. Nothing else about the response, such as its headers, is.
+    /// This context is part of the error's data, and also available through
+    /// `method`, `request_id` and `body_snippet`.
+    pub fn response_parse(method: Method, request_id: &Id, error: Error, body: &[u8]) -> Error {
+        let mut data = format!(
+            "invalid response to `{}` request {}",
+            method,
+            serde_json::to_string(request_id).unwrap()
+        );
+        if let Some(error_data) = &error.data {
+            data = format!("{}: {}", data, error_data);
+        }
+
+        let snippet = String::from_utf8_lossy(&body[..body.len().min(BODY_SNIPPET_LENGTH)]);
+        let ellipsis = if body.len() > BODY_SNIPPET_LENGTH {
+            "..."
+        } else {
+            ""
+        };
+
+        Error {
+            data: Some(format!("{} (body: {}{})", data, snippet, ellipsis)),
+            response: Some(Arc::new(ResponseContext {
+                method,
+                request_id: request_id.clone(),
+                body_snippet: snippet.into_owned(),
+            })),
+            ..error
+        }
+    }
+
     /// Create a new server error
     pub fn server_error<D>(data: D) -> Error
     where
@@ -78,6 +247,201 @@ impl Error {
         self.code
     }
 
+    /// Obtain the JSONRPC error code returned by the node, or `None` if this
+    /// error originated in the client (e.g. an HTTP or websocket failure, a
+    /// response which could not be parsed or an invalid option)
+    ///
+    /// This is the code of the error object the node sent, even if the error
+    /// has since been given a more specific `code`, e.g.
+    /// `Code::SubscriptionLimitReached`.
+    pub fn jsonrpc_code(&self) -> Option<Code> {
+        self.jsonrpc_code
+    }
+
+    /// Give this error a more specific code, keeping track of the code the
+    /// node sent it with, if any
+    pub(crate) fn reclassify(self, code: Code) -> Error {
+        Error {
+            code,
+            message: code.to_string(),
+            ..self
+        }
+    }
+
+    /// Mark this error as the JSONRPC error object sent by the node
+    pub(crate) fn sent_by_node(self) -> Error {
+        Error {
+            jsonrpc_code: Some(self.code),
+            ..self
+        }
+    }
+
+    /// Obtain the layer of the RPC stack at which this error occurred
+    ///
+    /// Codes which both the node and the client use, such as
+    /// `Code::InvalidParams`, are told apart by whether the error was sent by
+    /// the node, see `jsonrpc_code`.
+    pub fn layer(&self) -> Layer {
+        match self.code {
+            Code::HttpError
+            | Code::WebSocketError
+            | Code::Timeout
+            | Code::SubscriptionClosed
+            | Code::ServerBusy
+            | Code::ServerDroppedSlowClient => Layer::Transport,
+            Code::ParseError | Code::InvalidRequest | Code::ChainIdMismatch => Layer::Protocol,
+            Code::TxTooLarge => Layer::Client,
+            Code::SubscriptionLimitReached
+            | Code::MethodNotFound
+            | Code::InvalidParams
+            | Code::InternalError
+            | Code::ServerError
+            | Code::Other(_) => {
+                if self.jsonrpc_code.is_some() {
+                    Layer::Server
+                } else {
+                    Layer::Client
+                }
+            }
+        }
+    }
+
+    /// Recognize well-known errors reported by the node
+    ///
+    /// Tendermint reports most of these as internal errors, so they are told
+    /// apart by their message, which remains available through `data`.
+    pub fn kind(&self) -> Kind {
+        match self.code {
+            Code::SubscriptionLimitReached => return Kind::SubscriptionLimitReached,
+            Code::TxTooLarge => return Kind::TxTooLarge,
+            _ => (),
+        }
+
+        if !self.is_server_error() {
+            return Kind::Other;
+        }
+
+        let data = self.data().unwrap_or_default();
+
+        if data.starts_with("tx already exists in cache") {
+            Kind::TxInCache
+        } else if data.starts_with("mempool is full") {
+            Kind::MempoolFull
+        } else if data.starts_with("height ") && data.contains(" is not available") {
+            Kind::HeightNotAvailable
+        } else if data.starts_with("max_subscriptions_per_client") {
+            Kind::SubscriptionLimitReached
+        } else if data.starts_with("transaction indexing is disabled") {
+            Kind::TxIndexingDisabled
+        } else if data.starts_with("tx (") && data.ends_with(") not found") {
+            Kind::TxNotFound
+        } else if data.starts_with("tx size is too big") || data.starts_with("Tx too large") {
+            Kind::TxTooLarge
+        } else {
+            Kind::Server
+        }
+    }
+
+    // The following predicates are determined by the error's `Code` only, and
+    // can be relied upon regardless of how errors are displayed. At most one
+    // of them holds for any given error.
+
+    /// Did a request time out?
+    pub fn is_timeout(&self) -> bool {
+        self.code == Code::Timeout
+    }
+
+    /// Did the HTTP or websocket connection to the node fail?
+    pub fn is_connection_error(&self) -> bool {
+        matches!(self.code, Code::HttpError | Code::WebSocketError)
+    }
+
+    /// Did the node reject the request with an error object, i.e. is this
+    /// error at `Layer::Server`?
+    pub fn is_server_error(&self) -> bool {
+        self.layer() == Layer::Server
+    }
+
+    /// Was a message malformed, i.e. not valid JSON or not of the expected
+    /// shape?
+    pub fn is_parse_error(&self) -> bool {
+        self.code == Code::ParseError
+    }
+
+    /// Was a subscription closed, either by the node or because the websocket
+    /// was closed?
+    ///
+    /// Check for `Code::ServerDroppedSlowClient` to tell whether the node
+    /// dropped the client for not keeping up with events.
+    pub fn is_subscription_closed(&self) -> bool {
+        matches!(
+            self.code,
+            Code::SubscriptionClosed | Code::ServerDroppedSlowClient
+        )
+    }
+
+    /// Is it safe and useful to retry the request which failed with this
+    /// error?
+    ///
+    /// Failures which will recur, such as malformed responses or rejected
+    /// transactions, are never retryable.
+    pub fn is_retryable(&self) -> bool {
+        match self.code {
+            Code::Timeout
+            | Code::ServerBusy
+            | Code::SubscriptionClosed
+            | Code::ServerDroppedSlowClient => true,
+            Code::HttpError | Code::WebSocketError => !self.is_invalid_http_request(),
+            Code::InternalError => self.kind() == Kind::MempoolFull,
+            Code::ParseError
+            | Code::InvalidRequest
+            | Code::MethodNotFound
+            | Code::InvalidParams
+            | Code::ServerError
+            | Code::SubscriptionLimitReached
+            | Code::ChainIdMismatch
+            | Code::TxTooLarge
+            | Code::Other(_) => false,
+        }
+    }
+
+    /// How long the node asked us to wait before retrying, from the
+    /// `Retry-After` header of a 429 or 503 response
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.retry_after
+    }
+
+    /// Method of the request whose response could not be parsed
+    pub fn method(&self) -> Option<Method> {
+        self.response.as_ref().map(|response| response.method)
+    }
+
+    /// ID of the request whose response could not be parsed
+    pub fn request_id(&self) -> Option<&Id> {
+        self.response.as_ref().map(|response| &response.request_id)
+    }
+
+    /// Start of the body of the response which could not be parsed,
+    /// truncated to 256 bytes
+    pub fn body_snippet(&self) -> Option<&str> {
+        self.response
+            .as_ref()
+            .map(|response| response.body_snippet.as_str())
+    }
+
+    /// Did building the HTTP request fail? This will happen again on retry.
+    #[cfg(feature = "client")]
+    fn is_invalid_http_request(&self) -> bool {
+        self.source
+            .as_ref()
+            .map_or(false, |source| source.is::<http::Error>())
+    }
+
+    #[cfg(not(feature = "client"))]
+    fn is_invalid_http_request(&self) -> bool {
+        false
+    }
+
     /// Borrow the error message (if available)
     pub fn message(&self) -> &str {
         &self.message
@@ -104,31 +468,136 @@ impl Display for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(json_error: serde_json::Error) -> Error {
+        if json_error.is_io() {
+            // Reading the response failed, rather than parsing it
+            Error::from_source(Code::HttpError, std::io::Error::from(json_error))
+        } else {
+            Error::from_source(Code::ParseError, json_error)
+        }
+    }
+}
+
 #[cfg(feature = "client")]
 impl From<http::Error> for Error {
     fn from(http_error: http::Error) -> Error {
-        Error::http_error(http_error.to_string())
+        Error::from_source(Code::HttpError, http_error)
     }
 }
 
 #[cfg(feature = "client")]
 impl From<hyper::Error> for Error {
     fn from(hyper_error: hyper::Error) -> Error {
-        Error::http_error(hyper_error.to_string())
+        if hyper_error.is_timeout() {
+            Error::from_source(Code::Timeout, hyper_error)
+        } else {
+            Error::from_source(Code::HttpError, hyper_error)
+        }
     }
 }
 
 #[cfg(feature = "client")]
 impl From<WSError> for Error {
     fn from(websocket_error: WSError) -> Error {
-        Error::websocket_error(websocket_error.to_string())
+        match websocket_error {
+            WSError::ConnectionClosed | WSError::AlreadyClosed => {
+                Error::from_source(Code::SubscriptionClosed, websocket_error)
+            }
+            _ => Error::from_source(Code::WebSocketError, websocket_error),
+        }
     }
 }
 
+/// Layers of the RPC stack at which errors occur, from the lowest up, and
+/// the client itself.
+///
+/// Errors are classified by their `Code`, and by whether the node sent them,
+/// rather than by being variants of per-layer error types, as `Error` also
+/// serves as the JSONRPC error object sent by the node.
+///
+/// Useful for generic wrappers, such as retry logic, which need to reason
+/// about where a failure originated without knowing every error code:
+///
+/// ```
+/// use tendermint_rpc::error::{Error, Layer};
+///
+/// fn should_try_another_node(error: &Error) -> bool {
+///     match error.layer() {
+///         // The node could not be reached
+///         Layer::Transport => true,
+///         // The node does not speak the same protocol
+///         Layer::Protocol => true,
+///         // The node understood the request, and rejected it
+///         Layer::Server => false,
+///         // The request was never sent
+///         Layer::Client => false,
+///     }
+/// }
+///
+/// assert!(should_try_another_node(&Error::http_error("connection refused")));
+/// assert!(!should_try_another_node(&Error::invalid_params("invalid RPC address")));
+/// ```
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Layer {
+    /// The HTTP or websocket connection failed, timed out or was closed, or
+    /// the node was too busy to handle the request (HTTP 429 or 503)
+    Transport,
+
+    /// A message could not be understood, e.g. malformed JSON or an invalid
+    /// JSONRPC envelope, or came from a node on an unexpected chain
+    Protocol,
+
+    /// The node answered with a JSONRPC error object
+    Server,
+
+    /// The client rejected the request, or its own options, before sending
+    /// anything to the node
+    Client,
+}
+
+/// Well-known errors reported by the node, see `Error::kind`
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Kind {
+    /// The transaction was already submitted, and is in the mempool cache
+    TxInCache,
+
+    /// The node's mempool is full; the transaction may be resubmitted later
+    MempoolFull,
+
+    /// The requested height has been pruned, or is not yet committed
+    HeightNotAvailable,
+
+    /// The node's limit on subscriptions per client was reached
+    SubscriptionLimitReached,
+
+    /// The node does not index transactions, so they cannot be queried
+    TxIndexingDisabled,
+
+    /// The transaction has not been committed, or not yet indexed
+    TxNotFound,
+
+    /// The transaction is larger than the chain or the node's mempool allows,
+    /// as reported by the node or found by `endpoint::broadcast::check_tx_size`
+    /// before sending it (with `Code::TxTooLarge`)
+    TxTooLarge,
+
+    /// Any other error reported by the node
+    Server,
+
+    /// The error did not originate at the node, e.g. it could not be reached
+    Other,
+}
+
 /// Tendermint RPC error codes.
 ///
 /// See `func RPC*Error()` definitions in:
 /// <https://github.com/tendermint/tendermint/blob/master/rpc/jsonrpc/types/types.go>
+///
+/// The codes from `HttpError` to `TxTooLarge` are raised by the client only.
+/// They are given small positive values for display, but are never decoded
+/// from an error object sent by the node, which yields `Code::Other` for any
+/// value JSONRPC does not define.
 #[derive(Copy, Clone, Debug, Eq, Error, Hash, PartialEq, PartialOrd, Ord)]
 pub enum Code {
     /// Low-level HTTP error
@@ -139,6 +608,36 @@ pub enum Code {
     #[error("Websocket Error")]
     WebSocketError,
 
+    /// The node's limit on subscriptions per client was reached
+    #[error("Subscription limit reached")]
+    SubscriptionLimitReached,
+
+    /// Request timed out
+    #[error("Timeout")]
+    Timeout,
+
+    /// Subscription closed by the node, or websocket closed
+    #[error("Subscription closed")]
+    SubscriptionClosed,
+
+    /// The node is on another chain than the expected one
+    #[error("Chain ID mismatch")]
+    ChainIdMismatch,
+
+    /// The node is overloaded or rate limiting requests
+    #[error("Server busy")]
+    ServerBusy,
+
+    /// Subscription cancelled by the node because the client was not reading
+    /// events fast enough
+    #[error("Server dropped slow client")]
+    ServerDroppedSlowClient,
+
+    /// The client found a transaction too large for the chain, and did not
+    /// broadcast it
+    #[error("Transaction too large")]
+    TxTooLarge,
+
     /// Parse error i.e. invalid JSON (-32700)
     #[error("Parse error. Invalid JSON")]
     ParseError,
@@ -175,11 +674,12 @@ impl Code {
     }
 }
 
+// Only the codes defined by JSONRPC are decoded. The codes of errors raised by
+// the client are never sent by the node, so a node sending one of their
+// values does not make its error e.g. a timeout.
 impl From<i32> for Code {
     fn from(value: i32) -> Code {
         match value {
-            0 => Code::HttpError,
-            1 => Code::WebSocketError,
             -32700 => Code::ParseError,
             -32600 => Code::InvalidRequest,
             -32601 => Code::MethodNotFound,
@@ -196,6 +696,13 @@ impl From<Code> for i32 {
         match code {
             Code::HttpError => 0,
             Code::WebSocketError => 1,
+            Code::SubscriptionLimitReached => 2,
+            Code::Timeout => 3,
+            Code::SubscriptionClosed => 4,
+            Code::ChainIdMismatch => 5,
+            Code::ServerBusy => 6,
+            Code::ServerDroppedSlowClient => 7,
+            Code::TxTooLarge => 8,
             Code::ParseError => -32700,
             Code::InvalidRequest => -32600,
             Code::MethodNotFound => -32601,
@@ -223,6 +730,10 @@ impl Serialize for Code {
 mod tests {
     use super::Code;
     use super::Error;
+    use super::Layer;
+    use crate::endpoint::health;
+    use crate::{Id, Method, Response};
+    use std::time::Duration;
 
     #[test]
     fn test_serialize() {
@@ -236,4 +747,256 @@ mod tests {
         assert_eq!(res.code.value(), -32700);
         assert_eq!(res.data, Some("hello world".to_string()));
     }
+
+    /// Error object sent by the node with the given code and data
+    fn node_error(code: i32, data: &str) -> Error {
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "1",
+            "error": {"code": code, "message": Code::from(code).to_string(), "data": data},
+        });
+        health::Response::from_string(response.to_string()).unwrap_err()
+    }
+
+    #[test]
+    fn test_wire_codes() {
+        // Values of client-side codes sent by the node are not taken for them
+        for value in 0..=8 {
+            assert_eq!(Code::from(value), Code::Other(value));
+        }
+
+        let response = r#"{"jsonrpc":"2.0","id":"1","error":{"code":3,"message":"Timeout"}}"#;
+        let error = health::Response::from_string(response).unwrap_err();
+        assert_eq!(error.code(), Code::Other(3));
+        assert_eq!(error.jsonrpc_code(), Some(Code::Other(3)));
+        assert!(!error.is_timeout());
+
+        // Errors raised by the client have no JSONRPC code, even those sharing
+        // a code with errors the node may send
+        let json_error = serde_json::from_str::<u64>("\"not a number\"").unwrap_err();
+        for error in &[
+            Error::from(json_error),
+            Error::invalid_params("invalid RPC address"),
+            Error::timeout("deadline elapsed"),
+            Error::http_error("connection refused"),
+        ] {
+            assert_eq!(error.jsonrpc_code(), None, "{:?}", error);
+        }
+    }
+
+    #[test]
+    fn test_predicates() {
+        let errors = [
+            Error::timeout("deadline elapsed"),
+            Error::http_error("connection refused"),
+            Error::websocket_error("connection reset"),
+            node_error(-32000, "tx already exists in cache"),
+            node_error(-32603, ""),
+            node_error(-32603, "max_subscriptions_per_client 5 reached")
+                .reclassify(Code::SubscriptionLimitReached),
+            node_error(-1, ""),
+            Error::parse_error("expected value"),
+            Error::subscription_closed("web socket closed"),
+            // Neither reached the node, nor failed to
+            Error::invalid_params("invalid RPC address"),
+        ];
+
+        for error in &errors {
+            let predicates = [
+                error.is_timeout(),
+                error.is_connection_error(),
+                error.is_server_error(),
+                error.is_parse_error(),
+                error.is_subscription_closed(),
+            ];
+            assert!(
+                predicates.iter().filter(|holds| **holds).count() <= 1,
+                "{:?}",
+                error
+            );
+        }
+
+        assert!(errors[0].is_timeout());
+        assert!(errors[1].is_connection_error());
+        assert!(errors[3].is_server_error());
+        assert!(errors[4].is_server_error());
+        assert!(errors[5].is_server_error());
+        assert!(errors[6].is_server_error());
+        assert!(errors[7].is_parse_error());
+        assert!(errors[8].is_subscription_closed());
+        assert!(!errors[9].is_server_error());
+
+        // Server errors are exactly those at the server layer
+        for error in &errors {
+            assert_eq!(
+                error.is_server_error(),
+                error.layer() == Layer::Server,
+                "{:?}",
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_error_source() {
+        let json_error = serde_json::from_str::<u64>("\"not a number\"").unwrap_err();
+        let error = Error::from(json_error);
+
+        assert!(error.is_parse_error());
+        let source = std::error::Error::source(&error).unwrap();
+        assert!(source.downcast_ref::<serde_json::Error>().is_some());
+
+        // The source is not repeated in the error's own message
+        assert_eq!(
+            error.to_string(),
+            "Parse error. Invalid JSON (code: -32700)"
+        );
+        assert_eq!(error.data(), None);
+    }
+
+    #[test]
+    fn test_io_error_source() {
+        let io_error = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset");
+        let error = Error::from(serde_json::Error::io(io_error));
+
+        assert!(error.is_connection_error());
+        let source = std::error::Error::source(&error)
+            .unwrap()
+            .downcast_ref::<std::io::Error>()
+            .unwrap();
+        assert_eq!(source.kind(), std::io::ErrorKind::ConnectionReset);
+    }
+
+    #[test]
+    fn test_retryable() {
+        let cases = [
+            (Error::timeout("deadline elapsed"), true),
+            (Error::server_busy(429, None), true),
+            (Error::subscription_closed("web socket closed"), true),
+            (Error::http_error("connection refused"), true),
+            (Error::websocket_error("connection reset"), true),
+            (
+                node_error(-32603, "mempool is full: number of txs 5000 (max: 5000)"),
+                true,
+            ),
+            (node_error(-32603, "tx already exists in cache"), false),
+            (node_error(-32603, ""), false),
+            (Error::parse_error("expected value"), false),
+            (node_error(-32600, ""), false),
+            (node_error(-32601, "foo"), false),
+            (Error::invalid_params("height must be positive"), false),
+            (Error::tx_too_large(200_001, 200_000), false),
+            (node_error(-32000, "subscription was cancelled"), false),
+            (
+                node_error(-32603, "max_subscriptions_per_client 5 reached")
+                    .reclassify(Code::SubscriptionLimitReached),
+                false,
+            ),
+            (
+                Error::chain_id_mismatch("cosmoshub-3", "cosmoshub-2"),
+                false,
+            ),
+            (Error::new(Code::Other(-1), None), false),
+        ];
+
+        for (error, retryable) in &cases {
+            assert_eq!(error.is_retryable(), *retryable, "{:?}", error);
+        }
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let error = Error::server_busy(503, Some(Duration::from_secs(30)));
+        assert_eq!(error.retry_after(), Some(Duration::from_secs(30)));
+        assert_eq!(error.data(), Some("HTTP status 503"));
+
+        assert_eq!(Error::timeout("deadline elapsed").retry_after(), None);
+    }
+
+    #[test]
+    fn test_response_parse() {
+        let json_error = serde_json::from_str::<u64>("\"not a number\"").unwrap_err();
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":"1","result":"{}"}}"#,
+            "x".repeat(300)
+        );
+
+        let error = Error::response_parse(
+            Method::Status,
+            &Id::Str("1".to_owned()),
+            Error::from(json_error),
+            body.as_bytes(),
+        );
+
+        assert!(error.is_parse_error());
+        assert!(std::error::Error::source(&error).is_some());
+        assert_eq!(
+            error.data().unwrap(),
+            format!(
+                "invalid response to `status` request \"1\" (body: {}...)",
+                &body[..256]
+            )
+        );
+        assert_eq!(error.method(), Some(Method::Status));
+        assert_eq!(error.request_id(), Some(&Id::Str("1".to_owned())));
+        assert_eq!(error.body_snippet(), Some(&body[..256]));
+
+        // Other errors have no response context
+        assert_eq!(Error::timeout("deadline elapsed").method(), None);
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync + 'static>() {}
+
+        // Errors can be passed between tasks and kept in shared state
+        assert_send_sync::<Error>();
+    }
+
+    #[test]
+    fn test_layers() {
+        for error in &[
+            Error::http_error("connection refused"),
+            Error::websocket_error("connection reset"),
+            Error::timeout("deadline elapsed"),
+            Error::subscription_closed("web socket closed"),
+            Error::server_dropped_slow_client("client is not pulling messages fast enough"),
+            Error::server_busy("429 Too Many Requests", None),
+        ] {
+            assert_eq!(error.layer(), Layer::Transport, "{:?}", error);
+        }
+
+        for error in &[
+            Error::parse_error("expected value"),
+            node_error(-32700, "error unmarshalling request"),
+            node_error(-32600, ""),
+            Error::chain_id_mismatch("cosmoshub-4", "osmosis-1"),
+        ] {
+            assert_eq!(error.layer(), Layer::Protocol, "{:?}", error);
+        }
+
+        for error in &[
+            node_error(-32000, "tx already exists in cache"),
+            node_error(-32603, ""),
+            node_error(-32601, "foo"),
+            node_error(-32603, "max_subscriptions_per_client 5 reached")
+                .reclassify(Code::SubscriptionLimitReached),
+            node_error(-32602, "invalid height"),
+            node_error(-32099, ""),
+            node_error(3, ""),
+        ] {
+            assert_eq!(error.layer(), Layer::Server, "{:?}", error);
+        }
+
+        // The same codes, raised by the client
+        for error in &[
+            Error::invalid_params("invalid RPC address"),
+            Error::method_not_found("foo"),
+            Error::server_error("tx already exists in cache"),
+            Error::new(Code::Other(-32099), None),
+            Error::tx_too_large(200_001, 200_000),
+        ] {
+            assert_eq!(error.layer(), Layer::Client, "{:?}", error);
+        }
+    }
 }
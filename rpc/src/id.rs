@@ -2,6 +2,8 @@
 
 use getrandom::getrandom;
 use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicI64, Ordering};
 
 /// JSONRPC ID: request-specific identifier
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
@@ -30,13 +32,89 @@ impl Id {
     }
 }
 
+/// Strategy for generating the IDs of outgoing requests
+pub trait IdProvider: Debug + Send + Sync {
+    /// Generate the ID of the next request
+    fn next_id(&self) -> Id;
+}
+
+/// Random UUID v4 request IDs (the default)
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RandomUuidProvider;
+
+impl IdProvider for RandomUuidProvider {
+    fn next_id(&self) -> Id {
+        Id::uuid_v4()
+    }
+}
+
+/// Sequential numerical request IDs, starting from a seed
+///
+/// Useful for matching requests up with the node's logs, and for
+/// deterministic test transcripts.
+#[derive(Debug, Default)]
+pub struct SequentialProvider {
+    /// ID of the next request
+    next: AtomicI64,
+}
+
+impl SequentialProvider {
+    /// Create a provider whose first ID is `seed`
+    pub fn new(seed: i64) -> Self {
+        Self {
+            next: AtomicI64::new(seed),
+        }
+    }
+}
+
+impl IdProvider for SequentialProvider {
+    fn next_id(&self) -> Id {
+        Id::Num(self.next.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde::{de::DeserializeOwned, Serialize};
-    use std::fmt::Debug;
+    use std::collections::BTreeSet;
+    use std::sync::Arc;
+    use std::thread;
 
     use super::*;
 
+    #[test]
+    fn sequential_ids() {
+        let provider = SequentialProvider::new(42);
+        assert_eq!(provider.next_id(), Id::Num(42));
+        assert_eq!(provider.next_id(), Id::Num(43));
+
+        // Deterministic for a given seed
+        assert_eq!(SequentialProvider::new(42).next_id(), Id::Num(42));
+    }
+
+    #[test]
+    fn unique_ids_across_threads() {
+        let providers: Vec<Arc<dyn IdProvider>> = vec![
+            Arc::new(RandomUuidProvider),
+            Arc::new(SequentialProvider::new(0)),
+        ];
+
+        for provider in providers {
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    let provider = provider.clone();
+                    thread::spawn(move || (0..100).map(|_| provider.next_id()).collect::<Vec<_>>())
+                })
+                .collect();
+
+            let ids: BTreeSet<Id> = handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect();
+            assert_eq!(ids.len(), 400, "{:?}", provider);
+        }
+    }
+
     #[test]
     fn round_tripping_jsonrpc_id() {
         let str = r#""42""#;
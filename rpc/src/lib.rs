@@ -2,17 +2,31 @@
 
 #[cfg(feature = "client")]
 mod client;
+#[cfg(feature = "blocking")]
+pub use client::blocking::{self, BlockingClient, BlockingEventListener};
 #[cfg(feature = "client")]
-pub use client::{event_listener, Client};
+pub use client::{event_listener, Client, ClientBuilder, EventListenerBuilder};
 
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+pub mod batch;
+mod compat;
 pub mod endpoint;
 pub mod error;
 mod id;
 mod method;
 pub mod request;
 pub mod response;
+#[cfg(feature = "test-support")]
+pub mod test_support;
 mod version;
 
 pub use self::{
-    error::Error, id::Id, method::Method, request::Request, response::Response, version::Version,
+    compat::CompatMode,
+    error::Error,
+    id::{Id, IdProvider, RandomUuidProvider, SequentialProvider},
+    method::Method,
+    request::Request,
+    response::Response,
+    version::Version,
 };
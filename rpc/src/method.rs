@@ -51,6 +51,12 @@ pub enum Method {
     /// Get node status
     Status,
 
+    /// Get a committed transaction by its hash
+    Tx,
+
+    /// Search for committed transactions
+    TxSearch,
+
     /// Get validator info for a block
     Validators,
 
@@ -78,6 +84,8 @@ impl Method {
             Method::Health => "health",
             Method::NetInfo => "net_info",
             Method::Status => "status",
+            Method::Tx => "tx",
+            Method::TxSearch => "tx_search",
             Method::Validators => "validators",
             Method::Subscribe => "subscribe",
             Method::BroadcastEvidence => "broadcast_evidence",
@@ -103,6 +111,8 @@ impl FromStr for Method {
             "health" => Method::Health,
             "net_info" => Method::NetInfo,
             "status" => Method::Status,
+            "tx" => Method::Tx,
+            "tx_search" => Method::TxSearch,
             "validators" => Method::Validators,
             "subscribe" => Method::Subscribe,
             "broadcast_evidence" => Method::BroadcastEvidence,
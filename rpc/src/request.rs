@@ -1,11 +1,16 @@
 //! JSONRPC requests
 
 use super::{Id, Method, Version};
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde::ser::{Error as _, SerializeMap};
+use serde::{de::DeserializeOwned, Deserialize, Serialize, Serializer};
 use std::fmt::Debug;
 
 /// JSONRPC requests
-pub trait Request: Debug + DeserializeOwned + Serialize + Sized {
+///
+/// Tendermint expects parameters by name, so requests must serialize as a
+/// JSON object, e.g. a struct with named fields. Requests without parameters
+/// may be unit structs, whose parameters are sent as an empty object.
+pub trait Request: Debug + DeserializeOwned + Serialize + Sized + Send {
     /// Response type for this command
     type Response: super::response::Response;
 
@@ -14,10 +19,15 @@ pub trait Request: Debug + DeserializeOwned + Serialize + Sized {
 
     /// Serialize this request as JSON
     fn into_json(self) -> String {
-        serde_json::to_string_pretty(&Wrapper::new(self)).unwrap()
+        into_json_with_id(self, Id::uuid_v4())
     }
 }
 
+/// Serialize a request as JSON, with the given ID
+pub(crate) fn into_json_with_id<R: Request>(request: R, id: Id) -> String {
+    serde_json::to_string_pretty(&Wrapper::new(id, request)).unwrap()
+}
+
 /// JSONRPC request wrapper (i.e. message envelope)
 #[derive(Debug, Deserialize, Serialize)]
 struct Wrapper<R> {
@@ -31,18 +41,35 @@ struct Wrapper<R> {
     method: Method,
 
     /// Request parameters (i.e. request object)
+    #[serde(serialize_with = "serialize_params")]
     params: R,
 }
 
+/// Serialize request parameters as a JSON object, see `Request`
+fn serialize_params<R, S>(params: &R, serializer: S) -> Result<S::Ok, S::Error>
+where
+    R: Serialize,
+    S: Serializer,
+{
+    match serde_json::to_value(params).map_err(S::Error::custom)? {
+        serde_json::Value::Null => serializer.serialize_map(Some(0))?.end(),
+        serde_json::Value::Object(params) => params.serialize(serializer),
+        other => Err(S::Error::custom(format!(
+            "request parameters must be a JSON object, not {}",
+            other
+        ))),
+    }
+}
+
 impl<R> Wrapper<R>
 where
     R: Request,
 {
-    /// Create a new request wrapper from the given request
-    pub fn new(request: R) -> Self {
+    /// Create a new request wrapper from the given request and ID
+    pub fn new(id: Id, request: R) -> Self {
         Self {
             jsonrpc: Version::current(),
-            id: Id::uuid_v4(),
+            id,
             method: request.method(),
             params: request,
         }
@@ -1,25 +1,121 @@
 //! JSONRPC response types
 
-use super::{Error, Id, Version};
+use super::{Error, Id, Method, Version};
+use bytes::Bytes;
+#[cfg(feature = "client")]
+use serde::de::IgnoredAny;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::io::Read;
 
 /// JSONRPC responses
-pub trait Response: Serialize + DeserializeOwned + Sized {
+pub trait Response: Serialize + DeserializeOwned + Sized + Send {
     /// Parse a JSONRPC response from a JSON string
     fn from_string(response: impl AsRef<[u8]>) -> Result<Self, Error> {
-        let wrapper: Wrapper<Self> =
-            serde_json::from_slice(response.as_ref()).map_err(Error::parse_error)?;
+        let wrapper: Wrapper<Self> = serde_json::from_slice(response.as_ref())?;
         wrapper.into_result()
     }
 
     /// Parse a JSONRPC response from an `io::Reader`
     fn from_reader(reader: impl Read) -> Result<Self, Error> {
-        let wrapper: Wrapper<Self> = serde_json::from_reader(reader).map_err(Error::parse_error)?;
+        let wrapper: Wrapper<Self> = serde_json::from_reader(reader)?;
         wrapper.into_result()
     }
 }
 
+/// Parse the response to the request with the given method and ID
+///
+/// Unless `lenient`, the response's JSONRPC envelope is validated first, see
+/// `validate_envelope`.
+///
+/// If it cannot be parsed, the error reports the method, the request ID and
+/// the start of the response body, to help diagnose version mismatches.
+#[cfg(feature = "client")]
+pub(crate) fn parse_response<R: Response>(
+    method: Method,
+    request_id: &Id,
+    body: &[u8],
+    lenient: bool,
+) -> Result<R, Error> {
+    if !lenient {
+        validate_envelope(request_id, body)
+            .map_err(|error| Error::response_parse(method, request_id, error, body))?;
+    }
+
+    R::from_string(body).map_err(|error| {
+        // Errors returned by the node itself are about the request instead
+        if error.is_parse_error() && error.jsonrpc_code().is_none() {
+            Error::response_parse(method, request_id, error, body)
+        } else {
+            error
+        }
+    })
+}
+
+/// JSONRPC envelope of a response, with its result and error left unparsed
+#[cfg(feature = "client")]
+#[derive(Deserialize)]
+struct Envelope {
+    jsonrpc: Option<Version>,
+    id: Option<Id>,
+    result: Option<IgnoredAny>,
+    error: Option<IgnoredAny>,
+}
+
+/// Ensure a response is a valid JSONRPC 2.0 response to the request with the
+/// given ID, i.e. that it:
+///
+/// - has a `jsonrpc` version of `2.0`
+/// - has exactly one of `result` and `error`
+/// - has the ID of the request, of the same type, unless it is an error
+///   which has a null ID
+#[cfg(feature = "client")]
+fn validate_envelope(request_id: &Id, body: &[u8]) -> Result<(), Error> {
+    let envelope: Envelope = serde_json::from_slice(body)?;
+
+    match &envelope.jsonrpc {
+        Some(version) => version
+            .ensure_supported()
+            .map_err(|error| Error::invalid_envelope(error.data().unwrap_or_default()))?,
+        None => return Err(Error::invalid_envelope("missing 'jsonrpc' version")),
+    }
+
+    match (&envelope.result, &envelope.error) {
+        (Some(_), Some(_)) => {
+            return Err(Error::invalid_envelope("both 'result' and 'error' are set"))
+        }
+        (None, None) => {
+            return Err(Error::invalid_envelope(
+                "neither 'result' nor 'error' is set",
+            ))
+        }
+        _ => (),
+    }
+
+    let id = envelope.id.unwrap_or(Id::None);
+    if id != *request_id && !(id == Id::None && envelope.error.is_some()) {
+        return Err(Error::invalid_envelope(format!(
+            "ID {} does not match request ID {}",
+            serde_json::to_string(&id).unwrap(),
+            serde_json::to_string(request_id).unwrap()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Response parsed from the body the node sent, along with that body
+///
+/// Returned by `Client::perform_with_raw`, e.g. for audit logs which must keep
+/// the exact bytes the node sent rather than a re-serialization.
+#[derive(Clone, Debug)]
+pub struct WithRaw<R> {
+    /// The parsed response
+    pub parsed: R,
+
+    /// The response body exactly as sent by the node
+    pub raw: Bytes,
+}
+
 /// JSONRPC response wrapper (i.e. message envelope)
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Wrapper<R> {
@@ -51,19 +147,103 @@ where
         &self.id
     }
 
+    /// Is this a notification from the server, i.e. a message with a null ID
+    /// and neither a result nor an error?
+    pub(crate) fn is_notification(&self) -> bool {
+        self.id == Id::None && self.result.is_none() && self.error.is_none()
+    }
+
     /// Convert this wrapper into a result type
     pub fn into_result(self) -> Result<R, Error> {
         // Ensure we're using a supported RPC version
         self.version().ensure_supported()?;
 
         if let Some(error) = self.error {
-            Err(error)
+            Err(error.sent_by_node())
         } else if let Some(result) = self.result {
             Ok(result)
         } else {
-            Err(Error::server_error(
+            Err(Error::parse_error(
                 "server returned malformatted JSON (no 'result' or 'error')",
             ))
         }
     }
 }
+
+#[cfg(all(test, feature = "client"))]
+mod tests {
+    use super::*;
+
+    fn validate(body: &str) -> Result<(), Error> {
+        validate_envelope(&Id::Num(1), body.as_bytes())
+    }
+
+    fn violation(body: &str) -> String {
+        let error = validate(body).unwrap_err();
+        assert!(error.is_parse_error());
+        error.data().unwrap().to_owned()
+    }
+
+    #[test]
+    fn valid_envelopes() {
+        validate(r#"{"jsonrpc":"2.0","id":1,"result":{}}"#).unwrap();
+        validate(r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32603,"message":"Internal error"}}"#)
+            .unwrap();
+
+        // The node may not know which request an error is about
+        validate(r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32700,"message":"Parse error"}}"#)
+            .unwrap();
+    }
+
+    #[test]
+    fn missing_version() {
+        assert_eq!(
+            violation(r#"{"id":1,"result":{}}"#),
+            "invalid JSONRPC envelope: missing 'jsonrpc' version"
+        );
+    }
+
+    #[test]
+    fn unsupported_version() {
+        assert!(violation(r#"{"jsonrpc":"1.0","id":1,"result":{}}"#)
+            .starts_with("invalid JSONRPC envelope: server RPC version unsupported: '1.0'"));
+    }
+
+    #[test]
+    fn result_and_error() {
+        assert_eq!(
+            violation(
+                r#"{"jsonrpc":"2.0","id":1,"result":{},"error":{"code":-32603,"message":"Internal error"}}"#
+            ),
+            "invalid JSONRPC envelope: both 'result' and 'error' are set"
+        );
+    }
+
+    #[test]
+    fn neither_result_nor_error() {
+        assert_eq!(
+            violation(r#"{"jsonrpc":"2.0","id":1}"#),
+            "invalid JSONRPC envelope: neither 'result' nor 'error' is set"
+        );
+        assert_eq!(
+            violation(r#"{"jsonrpc":"2.0","id":1,"result":null}"#),
+            "invalid JSONRPC envelope: neither 'result' nor 'error' is set"
+        );
+    }
+
+    #[test]
+    fn mismatched_id() {
+        assert_eq!(
+            violation(r#"{"jsonrpc":"2.0","id":"1","result":{}}"#),
+            r#"invalid JSONRPC envelope: ID "1" does not match request ID 1"#
+        );
+        assert_eq!(
+            violation(r#"{"jsonrpc":"2.0","id":2,"result":{}}"#),
+            "invalid JSONRPC envelope: ID 2 does not match request ID 1"
+        );
+        assert_eq!(
+            violation(r#"{"jsonrpc":"2.0","result":{}}"#),
+            "invalid JSONRPC envelope: ID null does not match request ID 1"
+        );
+    }
+}
@@ -0,0 +1,317 @@
+//! Helpers for testing code which uses the RPC client without a node
+//!
+//! # Fixtures
+//!
+//! A fixture is a file named `<name>.json` holding a complete JSONRPC
+//! response exactly as a node sends it, envelope included:
+//!
+//! ```json
+//! {
+//!   "jsonrpc": "2.0",
+//!   "id": "",
+//!   "result": { "response": { "data": "GaiaApp", ... } }
+//! }
+//! ```
+//!
+//! Event fixtures hold a websocket frame carrying a subscription event, whose
+//! `result` is a `ResultEvent`: the `query` subscribed to, the event `data`
+//! tagged with its `type` (e.g. `tendermint/event/Tx`), and the `events` map.
+//! The fixtures of this crate in `rpc/tests/support` can serve as templates.
+//!
+//! Fixtures can be recorded from a live node with a `FixtureRecorder`.
+//!
+//! # Events
+//!
+//! `EventBuilder` builds events without a fixture, and `must_recv` and
+//! `must_not_recv` check the events an `EventListener` receives.
+
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tendermint::{abci, block};
+
+use crate::endpoint::{
+    abci_info, block as block_endpoint, block_results, commit, status, validators,
+};
+use crate::event_listener::{
+    composite_tx_events, EventDataTx, EventListener, EventSubscription, ResultEvent, TMEventData,
+    TxResult, TxResultResult, WrappedResultEvent,
+};
+use crate::{error::Code, Client, Error, Request};
+
+/// `NewBlock` event frame `EventBuilder::new_block` starts from
+const NEW_BLOCK_TEMPLATE: &str = include_str!("test_support/new_block.json");
+
+/// Read the fixture called `name` from `dir`
+///
+/// # Panics
+///
+/// Panics if the fixture cannot be read.
+pub fn read_json_fixture(dir: impl AsRef<Path>, name: &str) -> String {
+    let path = dir.as_ref().join(name.to_owned() + ".json");
+    fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("cannot read fixture {}: {}", path.display(), e))
+}
+
+/// Read the event fixture called `name` from `dir`
+///
+/// Attributes are decoded as sent by Tendermint v0.34, unless called within
+/// `CompatMode::scope`.
+///
+/// # Panics
+///
+/// Panics if the fixture cannot be read or does not hold an event.
+pub fn read_event_fixture(dir: impl AsRef<Path>, name: &str) -> ResultEvent {
+    parse_event(&read_json_fixture(dir, name))
+}
+
+/// Parse a websocket frame carrying an event
+fn parse_event(frame: &str) -> ResultEvent {
+    serde_json::from_str::<WrappedResultEvent>(frame)
+        .expect("fixture is not an event frame")
+        .into_result()
+        .expect("fixture is an error rather than an event")
+}
+
+/// Get the next event from `listener`, skipping frames which are not events
+///
+/// # Panics
+///
+/// Panics if no event is received within `timeout`, or if an error is.
+pub async fn must_recv(listener: &mut EventListener, timeout: Duration) -> ResultEvent {
+    tokio::time::timeout(timeout, next_event(listener))
+        .await
+        .unwrap_or_else(|_| panic!("no event received within {:?}", timeout))
+}
+
+/// Check that `listener` receives no event within `timeout`
+///
+/// # Panics
+///
+/// Panics if an event or an error is received.
+pub async fn must_not_recv(listener: &mut EventListener, timeout: Duration) {
+    if let Ok(event) = tokio::time::timeout(timeout, next_event(listener)).await {
+        panic!("unexpected event received: {:?}", event);
+    }
+}
+
+/// Wait for the next event from `listener`, panicking on errors
+async fn next_event(listener: &mut EventListener) -> ResultEvent {
+    loop {
+        match listener.get_event().await {
+            Ok(Some(event)) => return event,
+            Ok(None) => continue,
+            Err(e) => panic!("error while waiting for an event: {}", e),
+        }
+    }
+}
+
+/// Builds events as a node would send them to subscribers, without writing
+/// their JSON by hand
+#[derive(Copy, Clone, Debug)]
+pub struct EventBuilder;
+
+impl EventBuilder {
+    /// `NewBlock` event for an empty block at `height`
+    ///
+    /// Everything but the height of the block and of its last commit is
+    /// taken from a block of the `cosmoshub-2` chain.
+    pub fn new_block(height: impl Into<block::Height>) -> ResultEvent {
+        let height = height.into();
+        let mut event = parse_event(NEW_BLOCK_TEMPLATE);
+
+        if let TMEventData::EventDataNewBlock(data) = &mut event.data {
+            let block = data.block.as_mut().expect("template has a block");
+            block.header.height = height;
+
+            // The first block has no last commit
+            if height.value() > 1 {
+                if let Some(commit) = &mut block.last_commit {
+                    commit.height = block::Height::from(height.value() - 1);
+                }
+            } else {
+                block.last_commit = None;
+            }
+        }
+
+        event
+    }
+
+    /// `Tx` event for the transaction with the given hex-encoded `hash`,
+    /// which emitted `events`
+    ///
+    /// The transaction is the first one of the block at height 1, and its
+    /// body is empty; both can be changed on the returned event.
+    pub fn tx(hash: impl Into<String>, events: Vec<abci::Event>) -> ResultEvent {
+        let height = block::Height::from(1);
+        let composite = composite_tx_events(&hash.into(), height, &events);
+
+        ResultEvent {
+            query: "tm.event='Tx'".to_owned(),
+            data: TMEventData::EventDataTx(EventDataTx {
+                tx_result: TxResult {
+                    height,
+                    index: 0,
+                    tx: String::new(),
+                    result: TxResultResult {
+                        log: String::new(),
+                        gas_wanted: "0".to_owned(),
+                        gas_used: "0".to_owned(),
+                        events,
+                    },
+                },
+            }),
+            events: Some(composite),
+            received_at: None,
+        }
+    }
+}
+
+/// Records the responses of a live node as fixtures, e.g. to regenerate
+/// those of this crate after a Tendermint upgrade by running it against a
+/// `kvstore` node
+#[derive(Clone, Debug)]
+pub struct FixtureRecorder {
+    /// Client requests are sent with
+    client: Client,
+
+    /// Directory fixtures are written to
+    dir: PathBuf,
+
+    /// Names of the fields whose values are redacted
+    redacted: HashSet<String>,
+}
+
+impl FixtureRecorder {
+    /// Create a recorder sending requests with `client`, and writing
+    /// fixtures to `dir`
+    pub fn new(client: Client, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            client,
+            dir: dir.into(),
+            redacted: HashSet::new(),
+        }
+    }
+
+    /// Replace the value of every string field called `field`, at any depth,
+    /// with `"redacted"`, e.g. to keep node monikers or addresses out of
+    /// recorded fixtures
+    pub fn redact(mut self, field: impl Into<String>) -> Self {
+        self.redacted.insert(field.into());
+        self
+    }
+
+    /// Send `request`, and record the node's response as the fixture called
+    /// `name`, returning its path
+    ///
+    /// Fails if the request fails or the fixture cannot be written, in which
+    /// case the `io::Error` is the error's source.
+    pub async fn record<R: Request>(&self, name: &str, request: R) -> Result<PathBuf, Error> {
+        let raw = self.client.perform_with_raw(request).await?.raw;
+        self.write(name, serde_json::from_slice(&raw)?)
+    }
+
+    /// Record the responses used by the tests of this crate: `status`,
+    /// `abci_info`, and `block`, `block_results`, `commit` and `validators`
+    /// at each of `heights`
+    ///
+    /// Responses at the first height are recorded under the names above, and
+    /// those at later heights with the height appended, e.g. `block_12`.
+    pub async fn record_defaults(&self, heights: &[block::Height]) -> Result<Vec<PathBuf>, Error> {
+        let mut paths = vec![
+            self.record("status", status::Request).await?,
+            self.record("abci_info", abci_info::Request).await?,
+        ];
+
+        for (i, height) in heights.iter().enumerate() {
+            let fixture = |name: &str| match i {
+                0 => name.to_owned(),
+                _ => format!("{}_{}", name, height),
+            };
+
+            paths.push(
+                self.record(&fixture("block"), block_endpoint::Request::new(*height))
+                    .await?,
+            );
+            paths.push(
+                self.record(
+                    &fixture("block_results"),
+                    block_results::Request::new(*height),
+                )
+                .await?,
+            );
+            paths.push(
+                self.record(&fixture("commit"), commit::Request::new(*height))
+                    .await?,
+            );
+            paths.push(
+                self.record(&fixture("validators"), validators::Request::new(*height))
+                    .await?,
+            );
+        }
+
+        Ok(paths)
+    }
+
+    /// Subscribe to `subscription`, and record the websocket frames of the
+    /// next `count` events as the fixtures `<name>_0` to `<name>_<count - 1>`
+    ///
+    /// Frames are recorded as the node sent them, so that e.g. the base64
+    /// attributes of Tendermint v0.33 are kept.
+    pub async fn record_events(
+        &self,
+        name: &str,
+        subscription: EventSubscription,
+        count: usize,
+    ) -> Result<Vec<PathBuf>, Error> {
+        let mut listener = self.client.event_listener().await?;
+        listener.subscribe(subscription).await?;
+
+        let mut paths = Vec::with_capacity(count);
+        while paths.len() < count {
+            if let Some(event) = listener.get_event_with_raw().await? {
+                let frame = serde_json::from_slice(&event.raw)?;
+                paths.push(self.write(&format!("{}_{}", name, paths.len()), frame)?);
+            }
+        }
+
+        Ok(paths)
+    }
+
+    /// Write `fixture` as the fixture called `name`, redacted and with an
+    /// empty ID, returning its path
+    fn write(&self, name: &str, mut fixture: Value) -> Result<PathBuf, Error> {
+        // Request IDs differ on every run
+        fixture["id"] = json!("");
+        self.redact_value(&mut fixture);
+
+        let path = self.dir.join(name.to_owned() + ".json");
+        let json = serde_json::to_string_pretty(&fixture)? + "\n";
+        fs::write(&path, json).map_err(|e| {
+            let message = format!("cannot write fixture {}: {}", path.display(), e);
+            Error::from_source(Code::InternalError, io::Error::new(e.kind(), message))
+        })?;
+        Ok(path)
+    }
+
+    /// Redact the configured fields of `value`, recursively
+    fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                for (field, value) in fields.iter_mut() {
+                    if value.is_string() && self.redacted.contains(field) {
+                        *value = json!("redacted");
+                    } else {
+                        self.redact_value(value);
+                    }
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(|value| self.redact_value(value)),
+            _ => (),
+        }
+    }
+}
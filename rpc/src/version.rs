@@ -31,7 +31,7 @@ impl Version {
         if self.is_supported() {
             Ok(())
         } else {
-            Err(Error::server_error(&format!(
+            Err(Error::parse_error(&format!(
                 "server RPC version unsupported: '{}' (only '{}' supported)",
                 self.0, SUPPORTED_VERSION
             )))
@@ -0,0 +1,90 @@
+//! Property-based serialization tests of RPC responses
+
+#![cfg(feature = "arbitrary")]
+
+use proptest::prelude::*;
+use serde_json::{json, Value};
+
+use tendermint_rpc::{arbitrary, Response};
+
+/// Serialize `response`, parse it back as sent by a node, and check that it
+/// serializes the same way again
+fn round_trip<R: Response>(response: &R) -> Value {
+    let serialized = serde_json::to_value(response).unwrap();
+    let wire = json!({"jsonrpc": "2.0", "id": "", "result": serialized}).to_string();
+    let parsed = R::from_string(&wire).unwrap();
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), serialized);
+    serialized
+}
+
+proptest! {
+    #[test]
+    fn broadcast_tx_async(response in arbitrary::broadcast_tx_async()) {
+        round_trip(&response);
+    }
+
+    #[test]
+    fn broadcast_tx_sync(response in arbitrary::broadcast_tx_sync()) {
+        let serialized = round_trip(&response);
+
+        // Codes are numbers, hashes upper case hex
+        prop_assert_eq!(&serialized["code"], &json!(response.code.value()));
+        prop_assert_eq!(&serialized["hash"], &json!(response.hash.to_string()));
+    }
+
+    #[test]
+    fn broadcast_tx_commit(response in arbitrary::broadcast_tx_commit()) {
+        let serialized = round_trip(&response);
+
+        // Heights are strings
+        prop_assert_eq!(&serialized["height"], &json!(response.height.to_string()));
+    }
+
+    #[test]
+    fn block_results(response in arbitrary::block_results()) {
+        let serialized = round_trip(&response);
+        prop_assert_eq!(&serialized["height"], &json!(response.height.to_string()));
+    }
+
+    #[test]
+    fn validators(response in arbitrary::validators_response()) {
+        let serialized = round_trip(&response);
+        prop_assert_eq!(
+            serialized["validators"].as_array().map(Vec::len),
+            Some(response.validators.len())
+        );
+    }
+
+    #[test]
+    fn status(response in arbitrary::status()) {
+        let serialized = round_trip(&response);
+        let sync_info = &serialized["sync_info"];
+
+        prop_assert_eq!(
+            &sync_info["latest_block_height"],
+            &json!(response.sync_info.latest_block_height.to_string())
+        );
+        prop_assert_eq!(
+            sync_info["latest_block_hash"].is_null(),
+            response.sync_info.latest_block_hash.is_none()
+        );
+    }
+
+    #[test]
+    fn net_info(response in arbitrary::net_info()) {
+        let serialized = round_trip(&response);
+        prop_assert_eq!(&serialized["n_peers"], &json!(response.peers.len().to_string()));
+    }
+
+    #[test]
+    fn block(response in arbitrary::block()) {
+        let serialized = round_trip(&response);
+        let block = &response.block;
+
+        prop_assert_eq!(
+            &serialized["block"]["header"]["height"],
+            &json!(block.header.height.to_string())
+        );
+        prop_assert_eq!(block.last_commit.is_none(), block.header.height.value() == 1);
+    }
+}
@@ -0,0 +1,184 @@
+//! Blocking Tendermint RPC client tests against a local mock server
+
+#![cfg(feature = "blocking")]
+
+use async_tungstenite::tungstenite::Message;
+use futures::{SinkExt, StreamExt};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use tendermint::net;
+use tendermint_rpc::error::Code;
+use tendermint_rpc::event_listener::{EventSubscription, TMEventData};
+use tendermint_rpc::test_support::read_json_fixture;
+use tendermint_rpc::{blocking, BlockingClient};
+
+/// Directory holding the fixtures served by the mock servers
+const FIXTURES: &str = "./tests/support";
+
+/// Serve `fixture` to every request, with the request's ID, from a runtime on
+/// another thread
+fn mock_server(fixture: &str) -> net::Address {
+    let fixture: serde_json::Value =
+        serde_json::from_str(&read_json_fixture(FIXTURES, fixture)).unwrap();
+    let (address_tx, address_rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut runtime = tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async move {
+            let make_service = make_service_fn(move |_conn| {
+                let fixture = fixture.clone();
+
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                        let mut response = fixture.clone();
+
+                        async move {
+                            let body = hyper::body::to_bytes(request.into_body()).await?;
+                            // Websocket upgrade requests have no JSON body
+                            if let Ok(request) = serde_json::from_slice::<serde_json::Value>(&body)
+                            {
+                                response["id"] = request["id"].clone();
+                            }
+                            Ok::<_, hyper::Error>(Response::new(Body::from(response.to_string())))
+                        }
+                    }))
+                }
+            });
+
+            let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+            address_tx.send(server.local_addr()).unwrap();
+            server.await.unwrap();
+        });
+    });
+
+    format!("tcp://{}", address_rx.recv().unwrap())
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn status() {
+    let client = BlockingClient::new(mock_server("status"));
+
+    let status = client.status().unwrap();
+    assert_eq!(status.node_info.network.as_str(), "cosmoshub-2");
+
+    // The runtime is reused across requests
+    assert_eq!(client.chain_id().unwrap().as_str(), "cosmoshub-2");
+}
+
+#[test]
+fn wait_for_tx() {
+    let client = BlockingClient::new(mock_server("tx"));
+    let hash = "9F28904F9C0F3AB74A81CBA48E39124DA1C680B47FBFCBA0126870DB722BCC30"
+        .parse()
+        .unwrap();
+
+    // Already committed
+    let response = client.wait_for_tx(hash, Duration::from_secs(5)).unwrap();
+    assert_eq!(response.height.value(), 12);
+    assert_eq!(response.index, 1);
+}
+
+#[test]
+#[should_panic(expected = "blocking RPC client called from async code")]
+fn async_context() {
+    let client = blocking::HttpClient::new(mock_server("health"));
+    let mut runtime = tokio::runtime::Builder::new()
+        .basic_scheduler()
+        .build()
+        .unwrap();
+
+    runtime.block_on(async { client.health() }).unwrap();
+}
+
+/// Accept a single subscription over the websocket, send it each of `frames`
+/// and close the websocket, from a runtime on another thread
+fn mock_websocket(frames: &[&str]) -> net::Address {
+    let frames: Vec<_> = frames
+        .iter()
+        .map(|name| read_json_fixture(FIXTURES, name))
+        .collect();
+    let (address_tx, address_rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut runtime = tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async move {
+            let mut listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            address_tx.send(listener.local_addr().unwrap()).unwrap();
+
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = async_tungstenite::tokio::accept_async(stream)
+                .await
+                .unwrap();
+
+            let request = socket.next().await.unwrap().unwrap().into_data();
+            let request: serde_json::Value = serde_json::from_slice(&request).unwrap();
+            let response = serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": {}});
+            socket
+                .send(Message::text(response.to_string()))
+                .await
+                .unwrap();
+
+            // Sent under the subscription's ID, as the node does
+            for frame in frames {
+                let mut frame: serde_json::Value = serde_json::from_str(&frame).unwrap();
+                frame["id"] = request["id"].clone();
+                socket.send(Message::text(frame.to_string())).await.unwrap();
+            }
+            socket.close(None).await.unwrap();
+        });
+    });
+
+    format!("tcp://{}", address_rx.recv().unwrap())
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn event_listener() {
+    let client = BlockingClient::new(mock_websocket(&[
+        "event_tx_v0_34",
+        "event_new_block",
+        "event_error_slow_client",
+    ]));
+    let mut listener = client.event_listener().unwrap();
+    listener
+        .subscribe(EventSubscription::TransactionSubscription)
+        .unwrap();
+
+    let event = listener
+        .next_event(Duration::from_secs(5))
+        .unwrap()
+        .unwrap();
+    assert!(matches!(event.data, TMEventData::EventDataTx(_)));
+
+    // Being dropped for reading too slowly is reported, unlike the websocket
+    // then being closed, which ends the iteration
+    let events: Vec<_> = listener.collect();
+    assert_eq!(events.len(), 2);
+    assert!(matches!(
+        events[0].as_ref().unwrap().data,
+        TMEventData::EventDataNewBlock(_)
+    ));
+    assert_eq!(
+        events[1].as_ref().unwrap_err().code(),
+        Code::ServerDroppedSlowClient
+    );
+}
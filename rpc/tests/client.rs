@@ -0,0 +1,1324 @@
+//! Tendermint RPC client tests against a local mock server
+
+#![cfg(feature = "client")]
+
+use async_tungstenite::tungstenite::Message;
+use futures::{SinkExt, StreamExt};
+use hyper::server::conn::Http;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Request, Response, Server, StatusCode};
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::fs;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tendermint::net;
+use tendermint_rpc::endpoint::{block_results, health, tx_search};
+use tendermint_rpc::event_listener::{EventListener, EventMarker, EventSubscription, TMEventData};
+use tendermint_rpc::test_support::{must_not_recv, must_recv, read_json_fixture};
+use tendermint_rpc::{error::Code, Client, CompatMode, Id, Method, SequentialProvider};
+
+/// Directory holding the fixtures served by the mock servers
+const FIXTURES: &str = "./tests/support";
+
+/// Requests observed by the mock server
+#[derive(Debug, Default)]
+struct MockState {
+    /// Total number of requests received
+    requests: AtomicUsize,
+
+    /// Number of requests currently being handled
+    in_flight: AtomicUsize,
+
+    /// Highest number of requests handled concurrently
+    max_in_flight: AtomicUsize,
+
+    /// `User-Agent` of each request
+    user_agents: Mutex<Vec<String>>,
+
+    /// `Host` of each request
+    hosts: Mutex<Vec<String>>,
+
+    /// JSONRPC ID of each request
+    request_ids: Mutex<Vec<serde_json::Value>>,
+}
+
+/// Serve `fixture` to every request, with the request's ID, holding each one
+/// for `delay`
+fn mock_server(fixture: &str, delay: Duration) -> (net::Address, Arc<MockState>) {
+    let fixture: serde_json::Value =
+        serde_json::from_str(&read_json_fixture(FIXTURES, fixture)).unwrap();
+    let state = Arc::new(MockState::default());
+    let service_state = state.clone();
+
+    let make_service = make_service_fn(move |_conn| {
+        let fixture = fixture.clone();
+        let state = service_state.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                let mut response = fixture.clone();
+                let state = state.clone();
+
+                if let Some(user_agent) = request.headers().get(header::USER_AGENT) {
+                    state
+                        .user_agents
+                        .lock()
+                        .unwrap()
+                        .push(user_agent.to_str().unwrap().to_owned());
+                }
+
+                if let Some(host) = request.headers().get(header::HOST) {
+                    state
+                        .hosts
+                        .lock()
+                        .unwrap()
+                        .push(host.to_str().unwrap().to_owned());
+                }
+
+                async move {
+                    // Websocket upgrade requests have no JSON body
+                    let request_body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                    if let Ok(request) = serde_json::from_slice::<serde_json::Value>(&request_body)
+                    {
+                        state
+                            .request_ids
+                            .lock()
+                            .unwrap()
+                            .push(request["id"].clone());
+                        response["id"] = request["id"].clone();
+                    }
+
+                    state.requests.fetch_add(1, Ordering::SeqCst);
+                    let in_flight = state.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    state.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+
+                    tokio::time::delay_for(delay).await;
+
+                    state.in_flight.fetch_sub(1, Ordering::SeqCst);
+                    Ok::<_, Infallible>(Response::new(Body::from(response.to_string())))
+                }
+            }))
+        }
+    });
+
+    let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+    let address = format!("tcp://{}", server.local_addr()).parse().unwrap();
+    tokio::spawn(server);
+
+    (address, state)
+}
+
+#[tokio::test]
+async fn max_in_flight_requests() {
+    let (address, state) = mock_server("health", Duration::from_millis(50));
+    let client = Client::new(address).with_max_in_flight_requests(2);
+
+    let requests = (0..10).map(|_| {
+        let client = client.clone();
+        async move { client.health().await }
+    });
+
+    for result in futures::future::join_all(requests).await {
+        result.unwrap();
+    }
+
+    assert_eq!(state.requests.load(Ordering::SeqCst), 10);
+    assert!(state.max_in_flight.load(Ordering::SeqCst) <= 2);
+}
+
+#[test]
+#[should_panic(expected = "limit on requests in flight must be at least one")]
+fn no_requests_in_flight() {
+    let address = "tcp://127.0.0.1:26657".parse().unwrap();
+    Client::new(address).with_max_in_flight_requests(0);
+}
+
+#[tokio::test]
+async fn rate_limit() {
+    let (address, state) = mock_server("health", Duration::from_millis(0));
+    let client = Client::new(address).with_rate_limit(2);
+
+    // The first two requests use up the initial burst, the remaining four
+    // are sent half a second apart
+    let start = Instant::now();
+    for _ in 0..6 {
+        client.health().await.unwrap();
+    }
+
+    assert!(start.elapsed() >= Duration::from_secs(2));
+    assert_eq!(state.requests.load(Ordering::SeqCst), 6);
+}
+
+/// Websocket acknowledging every subscription, counting them
+/// Handshake and subscriptions observed by the websocket mock
+#[derive(Debug, Default)]
+struct SubscriptionState {
+    /// Number of subscribe requests received
+    subscriptions: AtomicUsize,
+
+    /// `User-Agent` of the handshake
+    user_agent: Mutex<Option<String>>,
+
+    /// `Host` of the handshake
+    host: Mutex<Option<String>>,
+
+    /// ID of each subscribe request
+    request_ids: Mutex<Vec<serde_json::Value>>,
+}
+
+/// Mock websocket acknowledging every subscription
+async fn mock_subscriptions() -> (net::Address, Arc<SubscriptionState>) {
+    let state = Arc::new(SubscriptionState::default());
+    let mock_state = state.clone();
+    let mut listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("tcp://{}", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let handshake_state = mock_state.clone();
+        let mut socket = async_tungstenite::tokio::accept_hdr_async(
+            stream,
+            move |request: &Request<()>,
+                  response: Response<()>|
+                  -> Result<_, Response<Option<String>>> {
+                let read = |name| {
+                    request
+                        .headers()
+                        .get(name)
+                        .map(|value: &header::HeaderValue| value.to_str().unwrap().to_owned())
+                };
+                *handshake_state.user_agent.lock().unwrap() = read(header::USER_AGENT);
+                *handshake_state.host.lock().unwrap() = read(header::HOST);
+                Ok(response)
+            },
+        )
+        .await
+        .unwrap();
+
+        while let Some(Ok(request)) = socket.next().await {
+            let request: serde_json::Value = serde_json::from_slice(&request.into_data()).unwrap();
+            mock_state.subscriptions.fetch_add(1, Ordering::SeqCst);
+            mock_state
+                .request_ids
+                .lock()
+                .unwrap()
+                .push(request["id"].clone());
+
+            let response = serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": {}});
+            socket
+                .send(Message::text(response.to_string()))
+                .await
+                .unwrap();
+        }
+    });
+
+    (address, state)
+}
+
+/// Mock websocket acknowledging a single subscription, then sending it each
+/// of `frames`
+async fn mock_websocket(frames: Vec<String>) -> net::Address {
+    let mut listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("tcp://{}", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut socket = async_tungstenite::tokio::accept_async(stream)
+            .await
+            .unwrap();
+
+        let request = socket.next().await.unwrap().unwrap().into_data();
+        let request: serde_json::Value = serde_json::from_slice(&request).unwrap();
+        let response = serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": {}});
+        socket
+            .send(Message::text(response.to_string()))
+            .await
+            .unwrap();
+
+        for frame in frames {
+            socket.send(Message::text(frame)).await.unwrap();
+        }
+        // Hold the websocket open until the client closes it
+        while let Some(Ok(_)) = socket.next().await {}
+    });
+
+    address
+}
+
+#[tokio::test]
+async fn receive_events() {
+    let address = mock_websocket(vec![
+        r#"{"jsonrpc":"2.0","id":null,"method":"heartbeat"}"#.to_owned(),
+        read_json_fixture(FIXTURES, "event_new_block"),
+    ])
+    .await;
+    let mut listener = EventListener::connect(address).await.unwrap();
+    listener
+        .subscribe(EventSubscription::BlockSubscription)
+        .await
+        .unwrap();
+
+    // Frames which are not events are skipped
+    let event = must_recv(&mut listener, Duration::from_secs(5)).await;
+    assert!(matches!(event.data, TMEventData::EventDataNewBlock(_)));
+    must_not_recv(&mut listener, Duration::from_millis(100)).await;
+}
+
+#[tokio::test]
+async fn new_block_header_events() {
+    let address = mock_websocket(vec![read_json_fixture(FIXTURES, "event_new_block_header")]).await;
+    let mut listener = EventListener::connect(address).await.unwrap();
+    listener.subscribe_new_block_headers().await.unwrap();
+
+    let event = must_recv(&mut listener, Duration::from_secs(5)).await;
+    assert_eq!(event.query, "tm.event='NewBlockHeader'");
+    assert!(matches!(
+        event.data,
+        TMEventData::EventDataNewBlockHeader(_)
+    ));
+}
+
+#[tokio::test]
+async fn all_events_subscription() {
+    assert_eq!(
+        EventSubscription::AllEventsSubscription.as_str(),
+        "tm.event EXISTS"
+    );
+
+    let address = mock_websocket(vec![
+        read_json_fixture(FIXTURES, "event_new_block"),
+        read_json_fixture(FIXTURES, "event_tx_v0_34"),
+    ])
+    .await;
+    let mut listener = EventListener::connect(address).await.unwrap();
+    listener
+        .subscribe(EventSubscription::AllEventsSubscription)
+        .await
+        .unwrap();
+
+    let event = must_recv(&mut listener, Duration::from_secs(5)).await;
+    assert!(matches!(event.data, TMEventData::EventDataNewBlock(_)));
+    let event = must_recv(&mut listener, Duration::from_secs(5)).await;
+    assert!(matches!(event.data, TMEventData::EventDataTx(_)));
+}
+
+/// Node at height 26 serving `/status`, `/block` and `/tx_search` over HTTP,
+/// and sending `frames` over its websocket once subscribed to
+async fn mock_resumable_node(frames: Vec<String>) -> net::Address {
+    let mut listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("tcp://{}", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let frames = frames.clone();
+
+            tokio::spawn(async move {
+                // Websocket handshakes are the only GET requests
+                let mut method = [0; 4];
+                stream.peek(&mut method).await.unwrap();
+
+                if &method == b"GET " {
+                    let mut socket = async_tungstenite::tokio::accept_async(stream)
+                        .await
+                        .unwrap();
+                    let request = socket.next().await.unwrap().unwrap().into_data();
+                    let request: serde_json::Value = serde_json::from_slice(&request).unwrap();
+                    let response =
+                        serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": {}});
+                    socket
+                        .send(Message::text(response.to_string()))
+                        .await
+                        .unwrap();
+
+                    for frame in frames {
+                        socket.send(Message::text(frame)).await.unwrap();
+                    }
+                    while let Some(Ok(_)) = socket.next().await {}
+                } else {
+                    let service = service_fn(serve_resumable_node);
+                    Http::new().serve_connection(stream, service).await.unwrap();
+                }
+            });
+        }
+    });
+
+    address
+}
+
+/// Answer a request to the node of `mock_resumable_node`
+async fn serve_resumable_node(request: Request<Body>) -> Result<Response<Body>, hyper::Error> {
+    let body = hyper::body::to_bytes(request.into_body()).await?;
+    let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    let method = request["method"].as_str().unwrap();
+    let mut response: serde_json::Value =
+        serde_json::from_str(&read_json_fixture(FIXTURES, method)).unwrap();
+    match method {
+        "status" => response["result"]["sync_info"]["latest_block_height"] = "26".into(),
+        "block" => {
+            response["result"]["block"]["header"]["height"] = request["params"]["height"].clone()
+        }
+        _ => (),
+    }
+    response["id"] = request["id"].clone();
+
+    Ok(Response::new(Body::from(response.to_string())))
+}
+
+/// Frame of the event in `fixture`, moved to the block at `height`
+fn event_frame(fixture: &str, height: &str) -> String {
+    let mut frame: serde_json::Value =
+        serde_json::from_str(&read_json_fixture(FIXTURES, fixture)).unwrap();
+    let value = &mut frame["result"]["data"]["value"];
+    if value["TxResult"].is_object() {
+        value["TxResult"]["height"] = height.into();
+    } else {
+        value["block"]["header"]["height"] = height.into();
+    }
+    frame.to_string()
+}
+
+/// Markers of the next `count` events received by `listener`
+async fn next_markers(listener: &mut EventListener, count: usize) -> Vec<EventMarker> {
+    let mut markers = Vec::new();
+    for _ in 0..count {
+        let event = must_recv(listener, Duration::from_secs(5)).await;
+        markers.push(event.marker().unwrap());
+    }
+    markers
+}
+
+#[tokio::test]
+async fn resume_transaction_subscription() {
+    // The node is at height 26, so the transaction at height 26 was also
+    // backfilled
+    let address = mock_resumable_node(vec![
+        event_frame("event_tx_v0_34", "26"),
+        event_frame("event_tx_v0_34", "27"),
+    ])
+    .await;
+    let client = Client::new(address.clone());
+    let mut listener = EventListener::connect(address).await.unwrap();
+
+    let resume_from = EventMarker::Tx {
+        height: 24u64.into(),
+        index: 0,
+    };
+    listener
+        .subscribe_from(
+            &client,
+            EventSubscription::TransactionSubscription,
+            Some(resume_from),
+        )
+        .await
+        .unwrap();
+
+    // The 2 transactions missed since the marker, then the live ones
+    let tx = |height: u64| EventMarker::Tx {
+        height: height.into(),
+        index: 0,
+    };
+    assert_eq!(
+        next_markers(&mut listener, 3).await,
+        vec![tx(25), tx(26), tx(27)]
+    );
+    must_not_recv(&mut listener, Duration::from_millis(100)).await;
+}
+
+#[tokio::test]
+async fn resume_block_subscription() {
+    let address = mock_resumable_node(vec![
+        event_frame("event_new_block", "26"),
+        event_frame("event_new_block", "27"),
+    ])
+    .await;
+    let client = Client::new(address.clone());
+    let mut listener = EventListener::connect(address).await.unwrap();
+
+    let resume_from = EventMarker::Block(24u64.into());
+    listener
+        .subscribe_from(
+            &client,
+            EventSubscription::BlockSubscription,
+            Some(resume_from),
+        )
+        .await
+        .unwrap();
+
+    let block = |height: u64| EventMarker::Block(height.into());
+    assert_eq!(
+        next_markers(&mut listener, 3).await,
+        vec![block(25), block(26), block(27)]
+    );
+    must_not_recv(&mut listener, Duration::from_millis(100)).await;
+}
+
+#[tokio::test]
+async fn resume_from_mismatched_marker() {
+    let address = mock_resumable_node(vec![]).await;
+    let client = Client::new(address.clone());
+    let mut listener = EventListener::connect(address).await.unwrap();
+
+    let error = listener
+        .subscribe_from(
+            &client,
+            EventSubscription::TransactionSubscription,
+            Some(EventMarker::Block(24u64.into())),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Code::InvalidParams);
+}
+
+#[tokio::test]
+async fn tx_search_per_page() {
+    let (address, state) = mock_server("tx_search", Duration::from_millis(0));
+    let client = Client::new(address);
+
+    // The node would clamp the page to 100 results, so it is not requested
+    let error = client
+        .tx_search(
+            "tx.height >= 24",
+            false,
+            1,
+            1000,
+            tx_search::Order::Ascending,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Code::InvalidParams);
+    assert_eq!(state.requests.load(Ordering::SeqCst), 0);
+
+    let response = client
+        .tx_search(
+            "tx.height >= 24",
+            false,
+            1,
+            tx_search::MAX_PER_PAGE,
+            tx_search::Order::Ascending,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.txs.len(), 3);
+    assert_eq!(state.requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn rate_limit_subscriptions() {
+    let (address, state) = mock_subscriptions().await;
+    let client = Client::new(address).with_rate_limit(2);
+    let mut listener = client.event_listener().await.unwrap();
+
+    // Subscriptions count against the same limit as other requests
+    let start = Instant::now();
+    for _ in 0..6 {
+        listener
+            .subscribe(EventSubscription::TransactionSubscription)
+            .await
+            .unwrap();
+    }
+
+    assert!(start.elapsed() >= Duration::from_secs(2));
+    assert_eq!(state.subscriptions.load(Ordering::SeqCst), 6);
+}
+
+#[tokio::test]
+async fn cached_chain_id() {
+    let (address, state) = mock_server("status", Duration::from_millis(0));
+    let client = Client::new(address);
+
+    assert_eq!(client.chain_id().await.unwrap().as_str(), "cosmoshub-2");
+    assert_eq!(
+        client.clone().chain_id().await.unwrap().as_str(),
+        "cosmoshub-2"
+    );
+
+    // Only the first call reaches the node
+    assert_eq!(state.requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn user_agent() {
+    let (address, state) = mock_server("health", Duration::from_millis(0));
+
+    Client::new(address.clone()).health().await.unwrap();
+    Client::new(address.clone())
+        .with_user_agent("my-relayer/1.0")
+        .health()
+        .await
+        .unwrap();
+
+    // The mock server does not speak websocket, but still sees the upgrade
+    // request
+    EventListener::connect_with_user_agent(address, "my-relayer/1.0")
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        *state.user_agents.lock().unwrap(),
+        vec![
+            concat!("tendermint-rpc/", env!("CARGO_PKG_VERSION")),
+            "my-relayer/1.0",
+            "my-relayer/1.0",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn host_override() {
+    let (address, state) = mock_server("health", Duration::from_millis(0));
+    let port = match address {
+        net::Address::Tcp { port, .. } => port,
+        other => panic!("expected a TCP address, got {}", other),
+    };
+
+    // `.invalid` names never resolve
+    let address = format!("tcp://tendermint.invalid:{}", port)
+        .parse()
+        .unwrap();
+    Client::new(address.clone()).health().await.unwrap_err();
+
+    let client =
+        Client::new(address).with_host_override("tendermint.invalid", [127, 0, 0, 1].into());
+    client.health().await.unwrap();
+
+    // The mock server does not speak websocket, but still sees the upgrade
+    // request
+    client.event_listener().await.unwrap_err();
+
+    let host = format!("tendermint.invalid:{}", port);
+    assert_eq!(*state.hosts.lock().unwrap(), vec![host.clone(), host]);
+}
+
+#[tokio::test]
+async fn fixture_recorder() {
+    use tendermint_rpc::endpoint::status;
+    use tendermint_rpc::test_support::FixtureRecorder;
+    use tendermint_rpc::Response;
+
+    let (address, _) = mock_server("status", Duration::from_millis(0));
+    let dir = std::env::temp_dir().join(format!("tendermint-rpc-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let recorder =
+        FixtureRecorder::new(Client::new(address.clone()), dir.clone()).redact("moniker");
+    let path = recorder.record("status", status::Request).await.unwrap();
+    assert_eq!(path, dir.join("status.json"));
+
+    // Recorded fixtures parse as the response they record
+    let status = status::Response::from_string(fs::read(&path).unwrap()).unwrap();
+    assert_eq!(status.node_info.network.as_str(), "cosmoshub-2");
+    assert_eq!(status.node_info.moniker.as_ref(), "redacted");
+
+    // Fixtures which cannot be written are reported
+    let recorder = FixtureRecorder::new(Client::new(address), dir.join("missing"));
+    let error = recorder
+        .record("status", status::Request)
+        .await
+        .unwrap_err();
+    assert!(std::error::Error::source(&error).is_some());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn fixture_recorder_events() {
+    use tendermint_rpc::test_support::FixtureRecorder;
+
+    let address = mock_websocket(vec![read_json_fixture(FIXTURES, "event_tx_v0_33")]).await;
+
+    let dir = std::env::temp_dir().join(format!("tendermint-rpc-events-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let recorder = FixtureRecorder::new(Client::new(address), dir.clone());
+    let paths = recorder
+        .record_events("event_tx", EventSubscription::TransactionSubscription, 1)
+        .await
+        .unwrap();
+    assert_eq!(paths, vec![dir.join("event_tx_0.json")]);
+
+    // The frame is recorded as the node sent it, base64 attributes included
+    let recorded: serde_json::Value =
+        serde_json::from_slice(&fs::read(&paths[0]).unwrap()).unwrap();
+    let sent: serde_json::Value =
+        serde_json::from_str(&read_json_fixture(FIXTURES, "event_tx_v0_33")).unwrap();
+    assert_eq!(recorded["result"], sent["result"]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn builder() {
+    let (address, state) = mock_server("status", Duration::from_millis(0));
+    let port = match address {
+        net::Address::Tcp { port, .. } => port,
+        other => panic!("expected a TCP address, got {}", other),
+    };
+
+    let client = Client::builder(
+        format!("tcp://tendermint.invalid:{}", port)
+            .parse()
+            .unwrap(),
+    )
+    .user_agent("my-relayer/1.0")
+    .host_override("tendermint.invalid", [127, 0, 0, 1].into())
+    .id_provider(SequentialProvider::new(7))
+    .expected_chain_id("cosmoshub-2".parse().unwrap())
+    .max_in_flight_requests(1)
+    .rate_limit(100)
+    .build()
+    .unwrap();
+    client.status().await.unwrap();
+
+    // The chain ID is checked before the request itself is sent
+    let host = format!("tendermint.invalid:{}", port);
+    assert_eq!(*state.hosts.lock().unwrap(), vec![host.clone(), host]);
+    assert_eq!(
+        *state.user_agents.lock().unwrap(),
+        vec!["my-relayer/1.0", "my-relayer/1.0"]
+    );
+    assert_eq!(
+        *state.request_ids.lock().unwrap(),
+        vec![serde_json::Value::from(7), serde_json::Value::from(8)]
+    );
+}
+
+#[test]
+fn builder_validation() {
+    let address: net::Address = "tcp://127.0.0.1:26657".parse().unwrap();
+    Client::builder(address.clone()).build().unwrap();
+
+    let errors = vec![
+        (
+            Client::builder("unix:///tmp/node.sock".parse().unwrap()).build(),
+            "invalid RPC address",
+        ),
+        (
+            Client::builder(address.clone())
+                .user_agent("my-relayer\n")
+                .build(),
+            "invalid User-Agent",
+        ),
+        (
+            Client::builder(address.clone())
+                .max_in_flight_requests(0)
+                .build(),
+            "invalid limit on requests in flight",
+        ),
+        (
+            Client::builder(address).rate_limit(0).build(),
+            "invalid rate limit",
+        ),
+    ];
+
+    for (result, expected) in errors {
+        let err = result.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidParams);
+        assert!(err.data().unwrap().starts_with(expected), "{}", err);
+    }
+}
+
+#[tokio::test]
+async fn event_listener_builder() {
+    let (address, state) = mock_subscriptions().await;
+    let port = match address {
+        net::Address::Tcp { port, .. } => port,
+        other => panic!("expected a TCP address, got {}", other),
+    };
+
+    let mut listener = EventListener::builder(
+        format!("tcp://tendermint.invalid:{}", port)
+            .parse()
+            .unwrap(),
+    )
+    .user_agent("my-relayer/1.0")
+    .host_override("tendermint.invalid", [127, 0, 0, 1].into())
+    .id_provider(SequentialProvider::new(7))
+    .large_frame_size(1 << 20)
+    .rate_limit(2)
+    .connect()
+    .await
+    .unwrap();
+
+    let start = Instant::now();
+    for _ in 0..4 {
+        listener
+            .subscribe(EventSubscription::TransactionSubscription)
+            .await
+            .unwrap();
+    }
+
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    assert_eq!(
+        state.host.lock().unwrap().as_deref(),
+        Some(format!("tendermint.invalid:{}", port).as_str())
+    );
+    assert_eq!(
+        state.user_agent.lock().unwrap().as_deref(),
+        Some("my-relayer/1.0")
+    );
+    assert_eq!(
+        *state.request_ids.lock().unwrap(),
+        (7..11).map(serde_json::Value::from).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn event_listener_builder_validation() {
+    let address: net::Address = "tcp://127.0.0.1:26657".parse().unwrap();
+
+    let errors = vec![
+        (
+            EventListener::builder("unix:///tmp/node.sock".parse().unwrap())
+                .connect()
+                .await,
+            "invalid RPC address",
+        ),
+        (
+            EventListener::builder(address.clone())
+                .user_agent("my-relayer\n")
+                .connect()
+                .await,
+            "invalid User-Agent",
+        ),
+        (
+            EventListener::builder(address)
+                .rate_limit(0)
+                .connect()
+                .await,
+            "invalid rate limit",
+        ),
+    ];
+
+    // Options are checked before connecting, so nothing needs to listen on
+    // the address
+    for (result, expected) in errors {
+        let err = match result {
+            Ok(_) => panic!("expected {:?} to be rejected", expected),
+            Err(err) => err,
+        };
+        assert_eq!(err.code(), Code::InvalidParams);
+        assert!(err.data().unwrap().starts_with(expected), "{}", err);
+    }
+}
+
+#[tokio::test]
+async fn connection_error_source() {
+    // Nothing listens on a port which was just released
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let address = format!("tcp://127.0.0.1:{}", port).parse().unwrap();
+
+    let error = Client::new(address).health().await.unwrap_err();
+    assert!(error.is_connection_error());
+
+    let source = std::error::Error::source(&error).unwrap();
+    assert!(source.downcast_ref::<hyper::Error>().unwrap().is_connect());
+}
+
+#[test]
+fn futures_are_send() {
+    fn assert_send<T: Send>(_: T) {}
+
+    // Futures are only created here, never polled
+    let address: net::Address = "tcp://127.0.0.1:26657".parse().unwrap();
+    let client = Client::new(address.clone());
+
+    assert_send(client.status());
+    assert_send(client.chain_id());
+    assert_send(client.commits(1u64.into()..=2u64.into()));
+    assert_send(client.perform(tendermint_rpc::endpoint::health::Request));
+    assert_send(async move {
+        let mut listener = EventListener::connect(address).await?;
+        listener
+            .subscribe(tendermint_rpc::event_listener::EventSubscription::BlockSubscription)
+            .await?;
+        listener.get_event().await
+    });
+}
+
+#[tokio::test]
+async fn raw_response() {
+    let (address, _) = mock_server("status", Duration::from_millis(0));
+    let client = Client::new(address).with_id_provider(SequentialProvider::new(0));
+
+    let response = client
+        .perform_with_raw(tendermint_rpc::endpoint::status::Request)
+        .await
+        .unwrap();
+
+    // What the mock server sent, i.e. the fixture with the request's ID
+    let mut sent: serde_json::Value =
+        serde_json::from_str(&read_json_fixture(FIXTURES, "status")).unwrap();
+    sent["id"] = 0.into();
+    assert_eq!(response.raw, sent.to_string().as_bytes());
+    assert_eq!(response.parsed.node_info.network.as_str(), "cosmoshub-2");
+}
+
+#[tokio::test]
+async fn compat_mode() {
+    let (address, _) = mock_server("block_results", Duration::from_millis(0));
+    let first_attribute = |response: block_results::Response| {
+        response.txs_results.unwrap()[0].events[0].attributes[0].clone()
+    };
+
+    let v0_33 = Client::builder(address.clone())
+        .compat_mode(CompatMode::V0_33)
+        .build()
+        .unwrap();
+    let attribute = first_attribute(v0_33.latest_block_results().await.unwrap());
+    assert_eq!(attribute.key.as_ref(), "action");
+    assert_eq!(attribute.value.as_ref(), "delegate");
+
+    // v0.34 attributes are plain strings, so base64 ones are kept as-is
+    let v0_34 = Client::new(address);
+    let attribute = first_attribute(v0_34.latest_block_results().await.unwrap());
+    assert_eq!(attribute.key.as_ref(), "YWN0aW9u");
+
+    let address = mock_websocket(vec![read_json_fixture(FIXTURES, "event_tx_v0_33")]).await;
+    let mut listener = EventListener::builder(address)
+        .compat_mode(CompatMode::V0_33)
+        .connect()
+        .await
+        .unwrap();
+    listener
+        .subscribe(EventSubscription::TransactionSubscription)
+        .await
+        .unwrap();
+
+    let event = must_recv(&mut listener, Duration::from_secs(5)).await;
+    let (_, abci_event) = event.abci_events()[0];
+    assert_eq!(abci_event.attributes[0].key.as_ref(), "creator");
+    assert_eq!(abci_event.attributes[0].value.as_ref(), "Cosmoshi Netowoko");
+}
+
+#[tokio::test]
+async fn request_ids() {
+    let (address, state) = mock_server("health", Duration::from_millis(10));
+
+    // Sequential IDs are unique and deterministic, even for concurrent
+    // requests (which may reach the node in any order)
+    let client = Client::new(address.clone()).with_id_provider(SequentialProvider::new(100));
+    let requests: Vec<_> = (0..10).map(|_| client.health()).collect();
+    for result in futures::future::join_all(requests).await {
+        result.unwrap();
+    }
+
+    let mut ids = state
+        .request_ids
+        .lock()
+        .unwrap()
+        .drain(..)
+        .collect::<Vec<_>>();
+    ids.sort_by_key(|id| id.as_i64().unwrap());
+    assert_eq!(
+        ids,
+        (100..110).map(serde_json::Value::from).collect::<Vec<_>>()
+    );
+
+    // Random IDs are unique too
+    let client = Client::new(address);
+    let requests: Vec<_> = (0..10).map(|_| client.health()).collect();
+    for result in futures::future::join_all(requests).await {
+        result.unwrap();
+    }
+
+    let ids = state.request_ids.lock().unwrap();
+    let unique: HashSet<_> = ids.iter().map(|id| id.as_str().unwrap()).collect();
+    assert_eq!(unique.len(), 10);
+}
+
+#[tokio::test]
+async fn shared_request_ids() {
+    let (address, state) = mock_subscriptions().await;
+    let client = Client::new(address).with_id_provider(SequentialProvider::new(7));
+
+    // Batches and subscriptions draw from the same sequence as requests
+    let mut batch = client.batch_request();
+    assert_eq!(batch.push(health::Request), Id::Num(7));
+    client
+        .event_listener()
+        .await
+        .unwrap()
+        .subscribe(EventSubscription::TransactionSubscription)
+        .await
+        .unwrap();
+    assert_eq!(batch.push(health::Request), Id::Num(9));
+
+    assert_eq!(
+        *state.request_ids.lock().unwrap(),
+        vec![serde_json::Value::from(8)]
+    );
+}
+
+#[cfg(feature = "metrics")]
+#[tokio::test]
+async fn metrics() {
+    use tendermint_rpc::Method;
+
+    let (address, _) = mock_server("health", Duration::from_millis(10));
+    let client = Client::new(address);
+
+    client.health().await.unwrap();
+    client.clone().health().await.unwrap();
+
+    let snapshot = client.metrics_snapshot();
+    let latency = snapshot[&Method::Health];
+    assert_eq!(latency.count, 2);
+    assert!(latency.min >= Duration::from_millis(10));
+    assert!(latency.min <= latency.average() && latency.average() <= latency.max);
+
+    // Only methods which were called are included
+    assert_eq!(snapshot.len(), 1);
+}
+
+#[tokio::test]
+async fn strict_envelopes() {
+    // Serves the fixture as is, i.e. with another ID than the request's
+    let make_service = make_service_fn(|_conn| async {
+        Ok::<_, Infallible>(service_fn(|_request: Request<Body>| async {
+            Ok::<_, Infallible>(Response::new(Body::from(read_json_fixture(
+                FIXTURES, "health",
+            ))))
+        }))
+    });
+    let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+    let address: net::Address = format!("tcp://{}", server.local_addr()).parse().unwrap();
+    tokio::spawn(server);
+
+    let error = Client::new(address.clone()).health().await.unwrap_err();
+    assert!(error.is_parse_error());
+    assert!(error
+        .data()
+        .unwrap()
+        .contains(r#"invalid JSONRPC envelope: ID "" does not match request ID"#));
+
+    Client::new(address)
+        .with_lenient_responses()
+        .health()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn response_parse_error() {
+    // A node answering with the wrong kind of response
+    let (address, _) = mock_server("commit", Duration::from_millis(0));
+
+    let error = Client::new(address).status().await.unwrap_err();
+    assert!(error.is_parse_error());
+
+    assert_eq!(error.method(), Some(Method::Status));
+    let request_id = error.request_id().unwrap();
+    assert!(matches!(request_id, Id::Str(_)));
+
+    // The start of the response echoes the request ID, and the rest of the
+    // commit is cut off
+    let snippet = error.body_snippet().unwrap();
+    assert_eq!(snippet.len(), 256);
+    assert!(snippet.contains(&serde_json::to_string(request_id).unwrap()));
+
+    let source = std::error::Error::source(&error).unwrap();
+    assert!(source.downcast_ref::<serde_json::Error>().is_some());
+}
+
+#[tokio::test]
+async fn expected_chain_id() {
+    let (address, state) = mock_server("status", Duration::from_millis(0));
+
+    let client =
+        Client::new(address.clone()).with_expected_chain_id("cosmoshub-2".parse().unwrap());
+    client.status().await.unwrap();
+
+    let client = Client::new(address).with_expected_chain_id("cosmoshub-3".parse().unwrap());
+    let error = client.status().await.unwrap_err();
+    assert_eq!(error.code(), Code::ChainIdMismatch);
+    assert_eq!(
+        error.data(),
+        Some("expected chain ID cosmoshub-3, node is on cosmoshub-2")
+    );
+
+    // The chain ID is checked once per client, and mismatched requests are
+    // never sent
+    client.status().await.unwrap_err();
+    assert_eq!(state.requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn server_busy() {
+    let make_service = make_service_fn(|_conn| async {
+        Ok::<_, Infallible>(service_fn(|_request: Request<Body>| async {
+            Response::builder()
+                .status(StatusCode::TOO_MANY_REQUESTS)
+                .header(header::RETRY_AFTER, "7")
+                .body(Body::from("slow down"))
+        }))
+    });
+    let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+    let address = format!("tcp://{}", server.local_addr()).parse().unwrap();
+    tokio::spawn(server);
+
+    let error = Client::new(address).health().await.unwrap_err();
+    assert_eq!(error.code(), Code::ServerBusy);
+    assert!(error.is_retryable());
+    assert_eq!(error.retry_after(), Some(Duration::from_secs(7)));
+}
+
+#[tokio::test]
+async fn commits() {
+    let fixture: serde_json::Value =
+        serde_json::from_str(&read_json_fixture(FIXTURES, "commit")).unwrap();
+
+    // Serves commits for any height but 2, which has been pruned. Lower
+    // heights are answered more slowly, so responses arrive out of order.
+    let make_service = make_service_fn(move |_conn| {
+        let fixture = fixture.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                let mut response = fixture.clone();
+
+                async move {
+                    let body = hyper::body::to_bytes(request.into_body()).await?;
+                    let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                    let height = request["params"]["height"].as_str().unwrap().to_owned();
+                    let delay = 50 - 10 * height.parse::<u64>().unwrap();
+                    tokio::time::delay_for(Duration::from_millis(delay)).await;
+
+                    if height == "2" {
+                        response = serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": request["id"],
+                            "error": {
+                                "code": -32603,
+                                "message": "Internal error",
+                                "data": "height 2 is not available, lowest height is 3"
+                            }
+                        });
+                    } else {
+                        response["id"] = request["id"].clone();
+                        let signed_header = &mut response["result"]["signed_header"];
+                        signed_header["header"]["height"] = height.clone().into();
+                        signed_header["commit"]["height"] = height.into();
+                    }
+
+                    Ok::<_, hyper::Error>(Response::new(Body::from(response.to_string())))
+                }
+            }))
+        }
+    });
+    let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+    let address = format!("tcp://{}", server.local_addr()).parse().unwrap();
+    tokio::spawn(server);
+
+    let commits = Client::new(address)
+        .commits(1u64.into()..=3u64.into())
+        .await;
+
+    assert_eq!(commits.len(), 3);
+    assert_eq!(commits[0].as_ref().unwrap().header.height.value(), 1);
+    assert_eq!(
+        commits[1].as_ref().unwrap_err().data(),
+        Some("height 2 is not available, lowest height is 3")
+    );
+    assert_eq!(commits[2].as_ref().unwrap().header.height.value(), 3);
+}
+
+/// Hash of the transaction in the `tx` fixture
+const TX_HASH: &str = "9F28904F9C0F3AB74A81CBA48E39124DA1C680B47FBFCBA0126870DB722BCC30";
+
+/// Requests observed by a mock node whose transaction gets committed
+#[derive(Debug, Default)]
+struct TxNodeState {
+    /// Number of `/tx` requests received
+    tx_lookups: AtomicUsize,
+
+    /// Queries subscribed to over the websocket
+    queries: Mutex<Vec<String>>,
+
+    /// Whether the transaction was committed yet
+    committed: AtomicBool,
+}
+
+/// Node serving `/tx` over HTTP, and, if `websocket` is set, subscriptions
+/// over its websocket
+///
+/// With a websocket, the transaction is committed a couple of blocks after
+/// each subscription, at which point its event is sent. Without one, websocket
+/// connections are closed right away, and the transaction is committed by the
+/// time it is looked up for the third time.
+async fn mock_tx_node(websocket: bool) -> (net::Address, Arc<TxNodeState>) {
+    let state = Arc::new(TxNodeState::default());
+    let node_state = state.clone();
+    let mut listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("tcp://{}", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let state = node_state.clone();
+
+            tokio::spawn(async move {
+                // Websocket handshakes are the only GET requests
+                let mut method = [0; 4];
+                stream.peek(&mut method).await.unwrap();
+
+                if &method == b"GET " {
+                    if websocket {
+                        serve_tx_subscription(stream, state).await;
+                    }
+                } else {
+                    let service = service_fn(move |request| serve_tx(request, state.clone()));
+                    Http::new().serve_connection(stream, service).await.unwrap();
+                }
+            });
+        }
+    });
+
+    (address, state)
+}
+
+/// Answer a `/tx` request, with the `tx` fixture once the transaction is
+/// committed
+async fn serve_tx(
+    request: Request<Body>,
+    state: Arc<TxNodeState>,
+) -> Result<Response<Body>, hyper::Error> {
+    let body = hyper::body::to_bytes(request.into_body()).await?;
+    let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(request["method"], "tx");
+
+    let lookups = state.tx_lookups.fetch_add(1, Ordering::SeqCst) + 1;
+    if state.queries.lock().unwrap().is_empty() && lookups >= 3 {
+        state.committed.store(true, Ordering::SeqCst);
+    }
+
+    let fixture = if state.committed.load(Ordering::SeqCst) {
+        "tx"
+    } else {
+        "error_tx_not_found"
+    };
+    let mut response: serde_json::Value =
+        serde_json::from_str(&read_json_fixture(FIXTURES, fixture)).unwrap();
+    response["id"] = request["id"].clone();
+
+    Ok(Response::new(Body::from(response.to_string())))
+}
+
+/// Accept a subscription, and commit the transaction a couple of blocks later
+async fn serve_tx_subscription(stream: tokio::net::TcpStream, state: Arc<TxNodeState>) {
+    let mut socket = async_tungstenite::tokio::accept_async(stream)
+        .await
+        .unwrap();
+
+    let request = socket.next().await.unwrap().unwrap().into_data();
+    let request: serde_json::Value = serde_json::from_slice(&request).unwrap();
+    let query = request["params"]["query"].clone();
+    state
+        .queries
+        .lock()
+        .unwrap()
+        .push(query.as_str().unwrap().to_owned());
+
+    let response = serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": {}});
+    socket
+        .send(Message::text(response.to_string()))
+        .await
+        .unwrap();
+
+    tokio::time::delay_for(Duration::from_millis(300)).await;
+    state.committed.store(true, Ordering::SeqCst);
+
+    let mut event: serde_json::Value =
+        serde_json::from_str(&read_json_fixture(FIXTURES, "event_tx_v0_34")).unwrap();
+    event["result"]["query"] = query;
+
+    // The client may have found the transaction without waiting for its event
+    if socket.send(Message::text(event.to_string())).await.is_ok() {
+        // Hold the websocket open until the client closes it
+        while let Some(Ok(_)) = socket.next().await {}
+    }
+}
+
+#[tokio::test]
+async fn wait_for_tx_subscription() {
+    let (address, state) = mock_tx_node(true).await;
+    let client = Client::new(address);
+    let hash = TX_HASH.parse().unwrap();
+
+    let response = client
+        .wait_for_tx(hash, Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(response.hash.to_string(), TX_HASH);
+    assert_eq!(response.height.value(), 12);
+    assert_eq!(
+        *state.queries.lock().unwrap(),
+        vec![format!("tm.event='Tx' AND tx.hash='{}'", TX_HASH)]
+    );
+
+    // Looked up once the subscription was made, and once its event arrived
+    assert_eq!(state.tx_lookups.load(Ordering::SeqCst), 2);
+
+    // Committed before subscribing, so found without waiting for an event
+    let start = Instant::now();
+    client
+        .wait_for_tx(hash, Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert!(start.elapsed() < Duration::from_millis(300));
+    assert_eq!(state.tx_lookups.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn wait_for_tx_polling() {
+    let (address, state) = mock_tx_node(false).await;
+    let client = Client::new(address);
+    let hash = TX_HASH.parse().unwrap();
+
+    // The websocket is unavailable, so the transaction is polled for until
+    // it is committed
+    let response = client
+        .wait_for_tx(hash, Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(response.height.value(), 12);
+    assert!(state.queries.lock().unwrap().is_empty());
+    assert_eq!(state.tx_lookups.load(Ordering::SeqCst), 3);
+
+    // Already committed
+    client
+        .wait_for_tx(hash, Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(state.tx_lookups.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn wait_for_tx_timeout() {
+    let (address, state) = mock_server("error_tx_not_found", Duration::from_millis(0));
+    let hash = TX_HASH.parse().unwrap();
+
+    let error = Client::new(address)
+        .wait_for_tx(hash, Duration::from_millis(600))
+        .await
+        .unwrap_err();
+    assert!(error.is_timeout());
+    assert!(state.requests.load(Ordering::SeqCst) >= 2);
+}
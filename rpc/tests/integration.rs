@@ -1,24 +1,143 @@
 //! Tendermint RPC tests
 
+use tendermint::abci;
+
+/// Clear the `index` flags only reported by Tendermint v0.34, so that events
+/// decoded from v0.33 and v0.34 fixtures can be compared
+fn without_index(events: &[abci::Event]) -> Vec<abci::Event> {
+    let mut events = events.to_vec();
+    for attribute in events
+        .iter_mut()
+        .flat_map(|event| event.attributes.iter_mut())
+    {
+        attribute.index = None;
+    }
+    events
+}
+
+/// Parameters sent with each type of request, which Tendermint expects by name
+mod requests {
+    use serde_json::{json, Value};
+    use tendermint::abci::{transaction, Transaction};
+    use tendermint_rpc::{endpoint, Request};
+
+    /// Parameters sent with `request`, ensuring they are a JSON object
+    fn params(request: impl Request) -> Value {
+        let wrapper: Value = serde_json::from_str(&request.into_json()).unwrap();
+        let params = wrapper["params"].clone();
+        assert!(params.is_object(), "{}", params);
+        params
+    }
+
+    #[test]
+    fn without_params() {
+        assert_eq!(params(endpoint::abci_info::Request), json!({}));
+        assert_eq!(params(endpoint::genesis::Request), json!({}));
+        assert_eq!(params(endpoint::health::Request), json!({}));
+        assert_eq!(params(endpoint::net_info::Request), json!({}));
+        assert_eq!(params(endpoint::status::Request), json!({}));
+    }
+
+    #[test]
+    fn with_params() {
+        assert_eq!(
+            params(endpoint::abci_query::Request::new(
+                None,
+                b"key".to_vec(),
+                Some(5u64.into()),
+                true
+            )),
+            json!({"data": "6b6579", "height": "5", "prove": true})
+        );
+        assert_eq!(
+            params(endpoint::block::Request::new(5u64.into())),
+            json!({"height": "5"})
+        );
+        assert_eq!(
+            params(endpoint::block::Request::default()),
+            json!({"height": null})
+        );
+        assert_eq!(
+            params(endpoint::block_results::Request::new(5u64.into())),
+            json!({"height": "5"})
+        );
+        assert_eq!(
+            params(endpoint::blockchain::Request::new(1u64.into(), 5u64.into())),
+            json!({"minHeight": "1", "maxHeight": "5"})
+        );
+        assert_eq!(
+            params(endpoint::commit::Request::new(5u64.into())),
+            json!({"height": "5"})
+        );
+        assert_eq!(
+            params(endpoint::validators::Request::new(5u64.into())),
+            json!({"height": "5"})
+        );
+        assert_eq!(
+            params(endpoint::subscribe::Request::new(
+                "tm.event='Tx'".to_owned()
+            )),
+            json!({"query": "tm.event='Tx'"})
+        );
+        assert_eq!(
+            params(endpoint::tx::Request::new(
+                transaction::Hash::new([0; 32]),
+                false
+            )),
+            json!({"hash": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=", "prove": false})
+        );
+        assert_eq!(
+            params(endpoint::tx_search::Request::new(
+                "tx.height >= 5",
+                false,
+                2,
+                100,
+                endpoint::tx_search::Order::Ascending
+            )),
+            json!({
+                "query": "tx.height >= 5",
+                "prove": false,
+                "page": "2",
+                "per_page": "100",
+                "order_by": "asc"
+            })
+        );
+
+        let tx = || Transaction::new(b"key=value".to_vec());
+        let tx_params = json!({"tx": "a2V5PXZhbHVl"});
+        assert_eq!(
+            params(endpoint::broadcast::tx_async::Request::new(tx())),
+            tx_params
+        );
+        assert_eq!(
+            params(endpoint::broadcast::tx_sync::Request::new(tx())),
+            tx_params
+        );
+        assert_eq!(
+            params(endpoint::broadcast::tx_commit::Request::new(tx())),
+            tx_params
+        );
+    }
+}
+
 mod endpoints {
-    use std::{fs, path::PathBuf};
     use tendermint::abci::Code;
+    use tendermint::block::{CommitSig, CommitSigs};
+    use tendermint::{validator, vote, Time};
 
-    use tendermint_rpc::{self as rpc, endpoint, Response};
+    use tendermint_rpc::test_support::read_json_fixture;
+    use tendermint_rpc::{self as rpc, endpoint, CompatMode, Response};
 
+    const FIXTURES: &str = "./tests/support";
     const EXAMPLE_APP: &str = "GaiaApp";
     const EXAMPLE_CHAIN: &str = "cosmoshub-2";
 
-    fn read_json_fixture(name: &str) -> String {
-        fs::read_to_string(PathBuf::from("./tests/support/").join(name.to_owned() + ".json"))
-            .unwrap()
-    }
-
     #[test]
     fn abci_info() {
-        let response = endpoint::abci_info::Response::from_string(&read_json_fixture("abci_info"))
-            .unwrap()
-            .response;
+        let response =
+            endpoint::abci_info::Response::from_string(&read_json_fixture(FIXTURES, "abci_info"))
+                .unwrap()
+                .response;
 
         assert_eq!(response.data.as_str(), EXAMPLE_APP);
         assert_eq!(response.last_block_height.value(), 488_120);
@@ -27,16 +146,47 @@ mod endpoints {
     #[test]
     fn abci_query() {
         let response =
-            endpoint::abci_query::Response::from_string(&read_json_fixture("abci_query"))
+            endpoint::abci_query::Response::from_string(&read_json_fixture(FIXTURES, "abci_query"))
                 .unwrap()
                 .response;
 
         assert_eq!(response.height.value(), 1);
+
+        let ops = response.proof.unwrap().ops;
+        assert_eq!(ops.len(), 2);
+        assert_eq!(ops[0].field_type, "iavl:v");
+        assert_eq!(ops[0].key, b"consensusState/ibconeclient/22".to_vec());
+        assert_eq!(ops[0].data.len(), 243);
+        assert_eq!(ops[1].field_type, "multistore");
+        assert_eq!(ops[1].key, b"ibc".to_vec());
+    }
+
+    #[test]
+    fn abci_query_v0_34() {
+        let v0_33 =
+            endpoint::abci_query::Response::from_string(&read_json_fixture(FIXTURES, "abci_query"))
+                .unwrap()
+                .response;
+        let v0_34 = endpoint::abci_query::Response::from_string(&read_json_fixture(
+            FIXTURES,
+            "abci_query_v0_34",
+        ))
+        .unwrap()
+        .response;
+
+        assert!(v0_34.proof.is_some());
+        assert_eq!(v0_33.proof, v0_34.proof);
+
+        // Proofs serialize in the node's format
+        let serialized = serde_json::to_value(&v0_34).unwrap();
+        assert_eq!(serialized["proof"]["ops"][0]["type"], "iavl:v");
+        assert_eq!(serialized["proof"]["ops"][1]["key"], "aWJj");
     }
 
     #[test]
     fn block() {
-        let response = endpoint::block::Response::from_string(&read_json_fixture("block")).unwrap();
+        let response =
+            endpoint::block::Response::from_string(&read_json_fixture(FIXTURES, "block")).unwrap();
 
         let tendermint::Block {
             header,
@@ -55,9 +205,11 @@ mod endpoints {
 
     #[test]
     fn block_with_evidences() {
-        let response =
-            endpoint::block::Response::from_string(&read_json_fixture("block_with_evidences"))
-                .unwrap();
+        let response = endpoint::block::Response::from_string(&read_json_fixture(
+            FIXTURES,
+            "block_with_evidences",
+        ))
+        .unwrap();
 
         let tendermint::Block { evidence, .. } = response.block;
         let evidence = evidence.iter().next().unwrap();
@@ -72,7 +224,7 @@ mod endpoints {
     // #[test]
     // fn block_empty_block_id() {
     //     let response =
-    //         endpoint::block::Response::from_string(&read_json_fixture("block_empty_block_id"))
+    //         endpoint::block::Response::from_string(&read_json_fixture(FIXTURES, "block_empty_block_id"))
     //             .unwrap();
     //
     //     let tendermint::Block { last_commit, .. } = response.block;
@@ -88,7 +240,8 @@ mod endpoints {
     #[test]
     fn first_block() {
         let response =
-            endpoint::block::Response::from_string(&read_json_fixture("first_block")).unwrap();
+            endpoint::block::Response::from_string(&read_json_fixture(FIXTURES, "first_block"))
+                .unwrap();
 
         let tendermint::Block {
             header,
@@ -108,9 +261,11 @@ mod endpoints {
     }
     #[test]
     fn block_results() {
-        let response =
-            endpoint::block_results::Response::from_string(&read_json_fixture("block_results"))
-                .unwrap();
+        let response = endpoint::block_results::Response::from_string(&read_json_fixture(
+            FIXTURES,
+            "block_results",
+        ))
+        .unwrap();
         assert_eq!(response.height.value(), 1814);
 
         let validator_updates = response.validator_updates;
@@ -127,10 +282,44 @@ mod endpoints {
         assert_eq!(validator_updates[0].power.value(), 1_233_243);
     }
 
+    #[test]
+    fn block_results_v0_33_and_v0_34() {
+        let v0_33 = CompatMode::V0_33
+            .scope(|| {
+                endpoint::block_results::Response::from_string(&read_json_fixture(
+                    FIXTURES,
+                    "block_results",
+                ))
+            })
+            .unwrap();
+        let v0_34 = endpoint::block_results::Response::from_string(&read_json_fixture(
+            FIXTURES,
+            "block_results_v0_34",
+        ))
+        .unwrap();
+
+        let v0_33_txs = v0_33.txs_results.unwrap();
+        let v0_34_txs = v0_34.txs_results.unwrap();
+        assert_eq!(v0_33_txs.len(), v0_34_txs.len());
+
+        for (tx_33, tx_34) in v0_33_txs.iter().zip(v0_34_txs.iter()) {
+            assert_eq!(tx_33.events, super::without_index(&tx_34.events));
+            assert_eq!(tx_33.gas_wanted, tx_34.gas_wanted);
+            assert_eq!(tx_33.gas_used, tx_34.gas_used);
+        }
+
+        let attribute = &v0_34_txs[0].events[0].attributes[0];
+        assert_eq!(attribute.key.as_ref(), "action");
+        assert_eq!(attribute.value.as_ref(), "delegate");
+        assert_eq!(attribute.index, Some(true));
+        assert_eq!(v0_33_txs[0].events[0].attributes[0].index, None);
+    }
+
     #[test]
     fn blockchain() {
         let response =
-            endpoint::blockchain::Response::from_string(&read_json_fixture("blockchain")).unwrap();
+            endpoint::blockchain::Response::from_string(&read_json_fixture(FIXTURES, "blockchain"))
+                .unwrap();
 
         assert_eq!(response.last_height.value(), 488_556);
         assert_eq!(response.block_metas.len(), 10);
@@ -142,6 +331,7 @@ mod endpoints {
     #[test]
     fn broadcast_tx_async() {
         let response = endpoint::broadcast::tx_async::Response::from_string(&read_json_fixture(
+            FIXTURES,
             "broadcast_tx_async",
         ))
         .unwrap();
@@ -155,6 +345,7 @@ mod endpoints {
     #[test]
     fn broadcast_tx_sync() {
         let response = endpoint::broadcast::tx_sync::Response::from_string(&read_json_fixture(
+            FIXTURES,
             "broadcast_tx_sync",
         ))
         .unwrap();
@@ -170,6 +361,7 @@ mod endpoints {
     #[test]
     fn broadcast_tx_sync_int() {
         let response = endpoint::broadcast::tx_sync::Response::from_string(&read_json_fixture(
+            FIXTURES,
             "broadcast_tx_sync_int",
         ))
         .unwrap();
@@ -185,6 +377,7 @@ mod endpoints {
     #[test]
     fn broadcast_tx_commit() {
         let response = endpoint::broadcast::tx_commit::Response::from_string(&read_json_fixture(
+            FIXTURES,
             "broadcast_tx_commit",
         ))
         .unwrap();
@@ -198,6 +391,7 @@ mod endpoints {
     #[test]
     fn broadcast_tx_commit_null_data() {
         let response = endpoint::broadcast::tx_commit::Response::from_string(&read_json_fixture(
+            FIXTURES,
             "broadcast_tx_commit_null_data",
         ))
         .unwrap();
@@ -211,7 +405,8 @@ mod endpoints {
     #[test]
     fn commit() {
         let response =
-            endpoint::commit::Response::from_string(&read_json_fixture("commit")).unwrap();
+            endpoint::commit::Response::from_string(&read_json_fixture(FIXTURES, "commit"))
+                .unwrap();
         let header = response.signed_header.header;
         assert_eq!(header.chain_id.as_ref(), EXAMPLE_CHAIN);
         // For now we just want to make sure the commit including precommits and a block_id exist
@@ -223,10 +418,69 @@ mod endpoints {
         assert_eq!(header.hash(), block_id.hash);
     }
 
+    #[test]
+    fn commit_voting_power() {
+        let mut response =
+            endpoint::commit::Response::from_string(&read_json_fixture(FIXTURES, "commit"))
+                .unwrap();
+        let signature = match &response.signed_header.commit.signatures[0] {
+            CommitSig::BlockIDFlagCommit { signature, .. } => *signature,
+            other => panic!("expected a commit signature, got {:?}", other),
+        };
+
+        // Total voting power of 100, of which 67 (just over 2/3) will sign
+        let validators: Vec<validator::Info> =
+            endpoint::validators::Response::from_string(&read_json_fixture(FIXTURES, "validators"))
+                .unwrap()
+                .validators
+                .into_iter()
+                .zip(&[34, 33, 1, 32])
+                .map(|(mut validator, power)| {
+                    validator.voting_power = vote::Power::new(*power);
+                    validator
+                })
+                .collect();
+        let validator_set = validator::Set::new(validators.clone());
+
+        let commit = |validator: &validator::Info| CommitSig::BlockIDFlagCommit {
+            validator_address: validator.address,
+            timestamp: Time::unix_epoch(),
+            signature,
+        };
+        let nil = |validator: &validator::Info| CommitSig::BlockIDFlagNil {
+            validator_address: validator.address,
+            timestamp: Time::unix_epoch(),
+            signature,
+        };
+
+        response.signed_header.commit.signatures = CommitSigs::new(vec![
+            commit(&validators[0]),
+            commit(&validators[1]),
+            CommitSig::BlockIDFlagAbsent,
+            nil(&validators[3]),
+        ]);
+        let voting_power = response.voting_power(&validator_set);
+        assert_eq!(voting_power.signed, 67);
+        assert_eq!(voting_power.total, 100);
+        assert!(voting_power.has_two_thirds_majority());
+
+        // Repeated signatures from the same validator are only counted once
+        response.signed_header.commit.signatures = CommitSigs::new(vec![
+            commit(&validators[0]),
+            commit(&validators[0]),
+            commit(&validators[2]),
+            nil(&validators[1]),
+        ]);
+        let voting_power = response.voting_power(&validator_set);
+        assert_eq!(voting_power.signed, 35);
+        assert!(!voting_power.has_two_thirds_majority());
+    }
+
     #[test]
     fn commit_height_1() {
         let response =
-            endpoint::commit::Response::from_string(&read_json_fixture("commit_1")).unwrap();
+            endpoint::commit::Response::from_string(&read_json_fixture(FIXTURES, "commit_1"))
+                .unwrap();
         let header = response.signed_header.header;
         let commit = response.signed_header.commit;
         let block_id = commit.block_id;
@@ -236,7 +490,8 @@ mod endpoints {
     #[test]
     fn genesis() {
         let response =
-            endpoint::genesis::Response::from_string(&read_json_fixture("genesis")).unwrap();
+            endpoint::genesis::Response::from_string(&read_json_fixture(FIXTURES, "genesis"))
+                .unwrap();
 
         let tendermint::Genesis {
             chain_id,
@@ -248,15 +503,43 @@ mod endpoints {
         assert_eq!(consensus_params.block.max_bytes, 200_000);
     }
 
+    #[test]
+    fn tx_size_precheck() {
+        use rpc::error::Kind;
+        use tendermint::abci::Transaction;
+
+        // Parameters fetched once and cached
+        let params =
+            endpoint::genesis::Response::from_string(&read_json_fixture(FIXTURES, "genesis"))
+                .unwrap()
+                .genesis
+                .consensus_params;
+
+        let tx = Transaction::new(vec![0; 200_000]);
+        endpoint::broadcast::check_tx_size(&tx, &params).unwrap();
+
+        let tx = Transaction::new(vec![0; 200_001]);
+        let err = endpoint::broadcast::check_tx_size(&tx, &params).unwrap_err();
+        assert_eq!(err.kind(), Kind::TxTooLarge);
+        assert_eq!(err.data(), Some("tx size is too big: 200001, max: 200000"));
+        assert!(!err.is_retryable());
+
+        // Unlike the node's own rejection, the pre-check never reached it
+        assert_eq!(err.code(), rpc::error::Code::TxTooLarge);
+        assert_eq!(err.jsonrpc_code(), None);
+        assert!(!err.is_server_error());
+    }
+
     #[test]
     fn health() {
-        endpoint::health::Response::from_string(&read_json_fixture("health")).unwrap();
+        endpoint::health::Response::from_string(&read_json_fixture(FIXTURES, "health")).unwrap();
     }
 
     #[test]
     fn net_info() {
         let response =
-            endpoint::net_info::Response::from_string(&read_json_fixture("net_info")).unwrap();
+            endpoint::net_info::Response::from_string(&read_json_fixture(FIXTURES, "net_info"))
+                .unwrap();
 
         assert_eq!(response.n_peers, 2);
         assert_eq!(response.peers[0].node_info.network.as_str(), EXAMPLE_CHAIN);
@@ -265,7 +548,8 @@ mod endpoints {
     #[test]
     fn status() {
         let response =
-            endpoint::status::Response::from_string(&read_json_fixture("status")).unwrap();
+            endpoint::status::Response::from_string(&read_json_fixture(FIXTURES, "status"))
+                .unwrap();
 
         assert_eq!(response.node_info.network.as_str(), EXAMPLE_CHAIN);
         assert_eq!(response.sync_info.latest_block_height.value(), 410_744);
@@ -275,7 +559,8 @@ mod endpoints {
     #[test]
     fn validators() {
         let response =
-            endpoint::validators::Response::from_string(&read_json_fixture("validators")).unwrap();
+            endpoint::validators::Response::from_string(&read_json_fixture(FIXTURES, "validators"))
+                .unwrap();
 
         assert_eq!(response.block_height.value(), 42);
 
@@ -283,9 +568,68 @@ mod endpoints {
         assert_eq!(validators.len(), 65);
     }
 
+    #[test]
+    fn tx() {
+        let response =
+            endpoint::tx::Response::from_string(&read_json_fixture(FIXTURES, "tx")).unwrap();
+
+        assert_eq!(
+            response.hash.to_string(),
+            "9F28904F9C0F3AB74A81CBA48E39124DA1C680B47FBFCBA0126870DB722BCC30"
+        );
+        assert_eq!(response.height.value(), 12);
+        assert_eq!(response.index, 1);
+        assert_eq!(response.tx_result.code, Code::Ok);
+        assert_eq!(response.tx_result.events[0].type_str, "app");
+        assert_eq!(response.tx.as_bytes(), b"name=satoshi");
+        assert!(response.proof.is_none());
+    }
+
+    #[test]
+    fn tx_with_proof() {
+        let response =
+            endpoint::tx::Response::from_string(&read_json_fixture(FIXTURES, "tx_with_proof"))
+                .unwrap();
+        let proof = response.proof.unwrap();
+
+        assert_eq!(proof.data, response.tx);
+        assert_eq!(proof.proof.total, 2);
+        assert_eq!(proof.proof.index, 1);
+        assert_eq!(proof.proof.aunts.len(), 1);
+
+        // The proof is against the root of the block's transactions
+        let txs = vec![b"name=nakamoto".to_vec(), b"name=satoshi".to_vec()];
+        assert_eq!(
+            proof.root_hash,
+            tendermint::merkle::simple_hash_from_byte_vectors(txs).to_vec()
+        );
+    }
+
+    #[test]
+    fn tx_search() {
+        let response =
+            endpoint::tx_search::Response::from_string(&read_json_fixture(FIXTURES, "tx_search"))
+                .unwrap();
+
+        assert_eq!(response.total_count, 3);
+        let positions = response
+            .txs
+            .iter()
+            .map(|tx| (tx.height.value(), tx.index))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![(24, 0), (25, 0), (26, 0)]);
+
+        // Each result is what `/tx` returns for the transaction
+        let tx = &response.txs[2];
+        assert_eq!(tx.tx.as_bytes(), b"name=satoshi");
+        assert_eq!(tx.tx_result.events[0].attributes[0].key.as_ref(), "creator");
+        assert!(tx.proof.is_none());
+    }
+
     #[test]
     fn jsonrpc_error() {
-        let result = endpoint::blockchain::Response::from_string(&read_json_fixture("error"));
+        let result =
+            endpoint::blockchain::Response::from_string(&read_json_fixture(FIXTURES, "error"));
 
         if let Err(err) = result {
             assert_eq!(err.code(), rpc::error::Code::InternalError);
@@ -298,4 +642,367 @@ mod endpoints {
             panic!("expected error, got {:?}", result)
         }
     }
+
+    #[test]
+    fn jsonrpc_error_codes() {
+        use rpc::error::Code;
+
+        for (fixture, code, message, data) in &[
+            (
+                "error_tx_in_cache",
+                Code::InternalError,
+                "Internal error",
+                Some("tx already exists in cache"),
+            ),
+            (
+                "error_method_not_found",
+                Code::MethodNotFound,
+                "Method not found",
+                Some(""),
+            ),
+            (
+                "error_invalid_params",
+                Code::InvalidParams,
+                "Invalid params",
+                Some("error converting json params to arguments: invalid character 'x' looking for beginning of value"),
+            ),
+            (
+                "error_parse",
+                Code::ParseError,
+                "Parse error. Invalid JSON",
+                Some("error unmarshalling request: unexpected end of JSON input"),
+            ),
+            ("error_no_data", Code::InternalError, "Internal error", None),
+        ] {
+            let err = endpoint::broadcast::tx_sync::Response::from_string(&read_json_fixture(
+                FIXTURES, fixture,
+            ))
+            .unwrap_err();
+
+            assert_eq!(err.jsonrpc_code(), Some(*code), "{}", fixture);
+            assert_eq!(err.message(), *message, "{}", fixture);
+            assert_eq!(err.data(), *data, "{}", fixture);
+        }
+
+        // Errors raised by the client itself carry no JSONRPC code
+        assert_eq!(
+            rpc::Error::http_error("connection refused").jsonrpc_code(),
+            None
+        );
+    }
+
+    #[test]
+    fn jsonrpc_error_kinds() {
+        use rpc::error::Kind;
+
+        for (fixture, kind) in &[
+            ("error_tx_in_cache", Kind::TxInCache),
+            ("error_mempool_full", Kind::MempoolFull),
+            ("error_height_not_available", Kind::HeightNotAvailable),
+            ("error_max_subscriptions", Kind::SubscriptionLimitReached),
+            ("error_tx_indexing_disabled", Kind::TxIndexingDisabled),
+            ("error_tx_too_large", Kind::TxTooLarge),
+            ("error_tx_not_found", Kind::TxNotFound),
+            ("error_method_not_found", Kind::Server),
+            ("error_no_data", Kind::Server),
+            ("error", Kind::Server),
+        ] {
+            let err = endpoint::broadcast::tx_sync::Response::from_string(&read_json_fixture(
+                FIXTURES, fixture,
+            ))
+            .unwrap_err();
+
+            assert_eq!(err.kind(), *kind, "{}", fixture);
+        }
+
+        // The raw message is kept
+        let err = endpoint::abci_query::Response::from_string(&read_json_fixture(
+            FIXTURES,
+            "error_height_not_available",
+        ))
+        .unwrap_err();
+        assert_eq!(
+            err.data(),
+            Some("height 1 is not available, lowest height is 1000")
+        );
+
+        assert_eq!(
+            rpc::Error::http_error("connection refused").kind(),
+            Kind::Other
+        );
+    }
+}
+
+mod event_listener {
+    use tendermint::abci;
+    use tendermint_rpc::event_listener::{ResultEvent, TMEventData};
+    use tendermint_rpc::test_support::{read_event_fixture, read_json_fixture, EventBuilder};
+    use tendermint_rpc::{endpoint, CompatMode, Response};
+
+    const FIXTURES: &str = "./tests/support";
+
+    #[test]
+    fn event_serialization_roundtrip() {
+        for fixture in &[
+            "event_new_block",
+            "event_new_block_header",
+            "event_tx_v0_33",
+            "event_tx_v0_34",
+            "event_tx_multiple_values",
+        ] {
+            let event = read_event_fixture(FIXTURES, fixture);
+            let serialized = serde_json::to_string(&event).unwrap();
+            let parsed: ResultEvent = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(event, parsed, "{} did not round-trip", fixture);
+
+            // The event type is serialized in the node's wire format
+            let original: serde_json::Value =
+                serde_json::from_str(&read_json_fixture(FIXTURES, fixture)).unwrap();
+            let serialized: serde_json::Value = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(
+                serialized["data"]["type"],
+                original["result"]["data"]["type"]
+            );
+        }
+    }
+
+    #[test]
+    fn new_block_event() {
+        let event = read_event_fixture(FIXTURES, "event_new_block");
+
+        assert_eq!(event.query, "tm.event='NewBlock'");
+        match event.data {
+            TMEventData::EventDataNewBlock(new_block) => {
+                let block = new_block.block.unwrap();
+                assert_eq!(block.header.height.value(), 10);
+                assert_eq!(block.header.chain_id.as_str(), "cosmoshub-2");
+            }
+            other => panic!("expected a NewBlock event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn new_block_header_event() {
+        let event = read_event_fixture(FIXTURES, "event_new_block_header");
+
+        assert_eq!(event.query, "tm.event='NewBlockHeader'");
+        let new_block_header = match event.data {
+            TMEventData::EventDataNewBlockHeader(new_block_header) => new_block_header,
+            other => panic!("expected a NewBlockHeader event, got {:?}", other),
+        };
+        assert_eq!(new_block_header.header.height.value(), 10);
+        assert_eq!(new_block_header.num_txs, 2);
+        assert_eq!(
+            new_block_header.header.time.to_rfc3339(),
+            "2020-03-15T16:57:08.151234567Z"
+        );
+
+        // The block time keeps its nanosecond precision when serialized
+        let serialized = serde_json::to_value(&new_block_header).unwrap();
+        assert_eq!(
+            serialized["header"]["time"],
+            "2020-03-15T16:57:08.151234567Z"
+        );
+        assert_eq!(serialized["num_txs"], "2");
+    }
+
+    #[test]
+    fn abci_events_from_subscriptions_and_block_results() {
+        let tx = match read_event_fixture(FIXTURES, "event_tx_v0_34").data {
+            TMEventData::EventDataTx(tx) => tx,
+            other => panic!("expected a Tx event, got {:?}", other),
+        };
+        let block_results = endpoint::block_results::Response::from_string(&read_json_fixture(
+            FIXTURES,
+            "block_results_v0_34",
+        ))
+        .unwrap();
+
+        // Both sources decode into the same ABCI event type
+        let events: Vec<&abci::Event> = tx
+            .tx_result
+            .result
+            .events
+            .iter()
+            .chain(&block_results.txs_results.unwrap()[0].events)
+            .collect();
+        assert_eq!(events[0].type_str, "app");
+        assert_eq!(events[1].type_str, "someevent1");
+        assert!(events
+            .iter()
+            .flat_map(|event| &event.attributes)
+            .all(|attribute| attribute.index == Some(true)));
+    }
+
+    #[test]
+    fn tx_event_multiple_values() {
+        let event = read_event_fixture(FIXTURES, "event_tx_multiple_values");
+        let events = event.events.as_ref().unwrap();
+
+        assert_eq!(events["tm.event"], vec!["Tx"]);
+        assert_eq!(
+            events["transfer.recipient"],
+            vec!["cosmos1recipienta", "cosmos1recipientb"]
+        );
+        assert_eq!(events["transfer.amount"], vec!["10uatom", "25uatom"]);
+
+        // Serializing the event reproduces the original events map
+        let fixture: serde_json::Value =
+            serde_json::from_str(&read_json_fixture(FIXTURES, "event_tx_multiple_values")).unwrap();
+        let serialized = serde_json::to_value(&event).unwrap();
+        assert_eq!(serialized["events"], fixture["result"]["events"]);
+    }
+
+    #[test]
+    fn tx_event_location() {
+        let tx = match read_event_fixture(FIXTURES, "event_tx_multiple_values").data {
+            TMEventData::EventDataTx(tx) => tx.tx_result,
+            other => panic!("expected a Tx event, got {:?}", other),
+        };
+
+        // The index is the position within the block, not the height
+        assert_eq!(tx.height.value(), 26);
+        assert_eq!(tx.index, 2);
+        assert_eq!(tx.tx_location(), (tx.height, 2));
+
+        // Both serialize back to their wire format
+        let serialized = serde_json::to_value(&tx).unwrap();
+        assert_eq!(serialized["height"], "26");
+        assert_eq!(serialized["index"], 2);
+    }
+
+    #[test]
+    fn tx_event_v0_33_and_v0_34() {
+        let v0_33 = CompatMode::V0_33.scope(|| read_event_fixture(FIXTURES, "event_tx_v0_33"));
+        let v0_34 = CompatMode::V0_34.scope(|| read_event_fixture(FIXTURES, "event_tx_v0_34"));
+
+        let (tx_33, mut tx_34) = match (v0_33.data, v0_34.data) {
+            (TMEventData::EventDataTx(tx_33), TMEventData::EventDataTx(tx_34)) => (tx_33, tx_34),
+            other => panic!("expected two Tx events, got {:?}", other),
+        };
+        assert_eq!(v0_33.events, v0_34.events);
+
+        let attributes = &tx_34.tx_result.result.events[0].attributes;
+        assert_eq!(attributes[0].key.as_ref(), "creator");
+        assert_eq!(attributes[0].value.as_ref(), "Cosmoshi Netowoko");
+        assert_eq!(attributes[0].index, Some(true));
+        assert_eq!(attributes[1].key.as_ref(), "key");
+        assert_eq!(attributes[1].value.as_ref(), "name");
+
+        tx_34.tx_result.result.events = super::without_index(&tx_34.tx_result.result.events);
+        assert_eq!(tx_33, tx_34);
+    }
+
+    #[test]
+    fn built_new_block_events() {
+        let block = |event: ResultEvent| match event.data {
+            TMEventData::EventDataNewBlock(new_block) => new_block.block.unwrap(),
+            other => panic!("expected a NewBlock event, got {:?}", other),
+        };
+
+        let first = block(EventBuilder::new_block(1u64));
+        assert_eq!(first.header.height.value(), 1);
+        assert!(first.last_commit.is_none());
+
+        let later = block(EventBuilder::new_block(42u64));
+        assert_eq!(later.header.height.value(), 42);
+        assert_eq!(later.last_commit.unwrap().height.value(), 41);
+    }
+
+    #[test]
+    fn built_tx_events() {
+        let fixture = read_event_fixture(FIXTURES, "event_tx_v0_34");
+        let events = match fixture.data {
+            TMEventData::EventDataTx(tx) => tx.tx_result.result.events,
+            other => panic!("expected a Tx event, got {:?}", other),
+        };
+
+        let event = EventBuilder::tx(
+            "9F28904F9C0F3AB74A81CBA48E39124DA1C680B47FBFCBA2126F3C8A9A6F8A1A",
+            events,
+        );
+
+        // The events map matches the one the node sent, but for the height
+        let mut expected = fixture.events.unwrap();
+        expected.insert("tx.height".to_owned(), vec!["1".to_owned()]);
+        assert_eq!(event.events.as_ref(), Some(&expected));
+
+        // Built events survive the wire format
+        let serialized = serde_json::to_string(&event).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ResultEvent>(&serialized).unwrap(),
+            event
+        );
+    }
+
+    #[test]
+    fn canonical_event_order() {
+        let tx = |height: u64, index: u32| {
+            let mut event = EventBuilder::tx("", vec![]);
+            if let TMEventData::EventDataTx(tx) = &mut event.data {
+                tx.tx_result.height = height.into();
+                tx.tx_result.index = index;
+            }
+            event
+        };
+        let generic = ResultEvent {
+            query: "tm.event='ValidatorSetUpdates'".to_owned(),
+            data: TMEventData::GenericJSONEvent(serde_json::json!({
+                "type": "tendermint/event/ValidatorSetUpdates",
+                "value": {},
+            })),
+            events: None,
+            received_at: None,
+        };
+
+        let mut events = vec![
+            tx(2, 0),
+            generic.clone(),
+            tx(1, 1),
+            EventBuilder::new_block(2u64),
+            tx(1, 0),
+            EventBuilder::new_block(1u64),
+        ];
+        events.sort_by(ResultEvent::cmp_position);
+
+        assert_eq!(
+            events,
+            vec![
+                EventBuilder::new_block(1u64),
+                tx(1, 0),
+                tx(1, 1),
+                EventBuilder::new_block(2u64),
+                tx(2, 0),
+                generic,
+            ]
+        );
+    }
+
+    #[test]
+    fn canonical_abci_event_order() {
+        let tx = read_event_fixture(FIXTURES, "event_tx_multiple_values");
+        let mut next_tx = tx.clone();
+        if let TMEventData::EventDataTx(data) = &mut next_tx.data {
+            data.tx_result.index += 1;
+        }
+
+        let expected: Vec<_> = tx
+            .abci_events()
+            .into_iter()
+            .chain(next_tx.abci_events())
+            .collect();
+        assert!(expected.len() > 2);
+
+        let mut events = expected.clone();
+        events.reverse();
+        events.sort_by_key(|(position, _)| *position);
+        assert_eq!(events, expected);
+
+        // Transactions come before their own ABCI events, and after those of
+        // earlier transactions
+        assert!(tx.position() < expected[0].0);
+        assert!(expected
+            .iter()
+            .any(|(position, _)| *position < next_tx.position()));
+    }
 }
@@ -60,11 +60,12 @@ pub struct DeliverTx {
     pub info: Info,
 
     /// Amount of gas wanted
-    #[serde(rename = "gasWanted")]
+    // Tendermint v0.34 switched to snake case for the gas fields
+    #[serde(rename = "gasWanted", alias = "gas_wanted")]
     pub gas_wanted: Gas,
 
     /// Amount of gas used
-    #[serde(rename = "gasUsed")]
+    #[serde(rename = "gasUsed", alias = "gas_used")]
     pub gas_used: Gas,
 
     /// Events
@@ -75,7 +76,7 @@ pub struct DeliverTx {
 }
 
 /// Event
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Event {
     /// Event type
     #[serde(rename = "type")]
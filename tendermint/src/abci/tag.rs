@@ -1,22 +1,46 @@
 //! Tags
 
-use crate::error::Error;
+use crate::{error::Error, serializers};
 use serde::{Deserialize, Serialize};
 use std::{fmt, str::FromStr};
 
 /// Tags
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Tag {
     /// Key
     pub key: Key,
 
     /// Value
     pub value: Value,
+
+    /// Whether the node indexes this tag
+    ///
+    /// Only reported by Tendermint v0.34 and later, `None` otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<bool>,
+}
+
+impl Tag {
+    /// Create a tag whose indexing is not reported, as sent by Tendermint
+    /// v0.33
+    ///
+    /// Prefer this over a struct literal, which breaks whenever a field is
+    /// added.
+    pub fn new(key: Key, value: Value) -> Self {
+        Tag {
+            key,
+            value,
+            index: None,
+        }
+    }
 }
 
 /// Tag keys
+///
+/// Deserialized as a plain string, as sent by Tendermint v0.34, unless
+/// another encoding is selected with `serializers::with_attribute_encoding`.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize)]
-pub struct Key(String);
+pub struct Key(#[serde(deserialize_with = "serializers::parse_attribute_string")] String);
 
 impl AsRef<str> for Key {
     fn as_ref(&self) -> &str {
@@ -39,8 +63,11 @@ impl fmt::Display for Key {
 }
 
 /// Tag values
+///
+/// Deserialized as a plain string, as sent by Tendermint v0.34, unless
+/// another encoding is selected with `serializers::with_attribute_encoding`.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
-pub struct Value(String);
+pub struct Value(#[serde(deserialize_with = "serializers::parse_attribute_string")] String);
 
 impl AsRef<str> for Value {
     fn as_ref(&self) -> &str {
@@ -3,6 +3,7 @@
 mod hash;
 
 pub use self::hash::Hash;
+use crate::{merkle::proof::SimpleProof, serializers};
 use std::slice;
 use {
     serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer},
@@ -61,7 +62,7 @@ impl Serialize for Transaction {
 /// transactions are arbitrary byte arrays.
 ///
 /// <https://github.com/tendermint/spec/blob/d46cd7f573a2c6a2399fcab2cde981330aa63f37/spec/core/data_structures.md#data>
-#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Default)]
 pub struct Data {
     txs: Option<Vec<Transaction>>,
 }
@@ -93,3 +94,17 @@ impl AsRef<[Transaction]> for Data {
         self.txs.as_deref().unwrap_or_else(|| &[])
     }
 }
+
+/// Proof of the inclusion of a transaction in a block, against the block's
+/// data hash
+/// <https://github.com/tendermint/tendermint/blob/c8483531d8e756f7fbb812db1dd16d841cdf298a/types/tx.go#L77>
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TxProof {
+    /// Merkle root of the block's transactions
+    #[serde(with = "serializers::bytes::hexstring")]
+    pub root_hash: Vec<u8>,
+    /// The transaction
+    pub data: Transaction,
+    /// Proof of the transaction's inclusion in the Merkle tree
+    pub proof: SimpleProof,
+}
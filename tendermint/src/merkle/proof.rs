@@ -18,7 +18,7 @@ pub struct Proof {
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct ProofOp {
     /// Type of the ProofOp
-    #[serde(alias = "type")]
+    #[serde(rename = "type", alias = "field_type")]
     pub field_type: String,
     /// Key of the ProofOp
     #[serde(default, with = "serializers::bytes::base64string")]
@@ -28,6 +28,26 @@ pub struct ProofOp {
     pub data: Vec<u8>,
 }
 
+/// Proof of the inclusion of a leaf in a simple Merkle tree, given by the
+/// hashes of its aunts on the path to the root
+/// <https://github.com/tendermint/tendermint/blob/c8483531d8e756f7fbb812db1dd16d841cdf298a/crypto/merkle/proof.go#L21>
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct SimpleProof {
+    /// Number of leaves in the tree
+    #[serde(with = "serializers::from_str")]
+    pub total: u64,
+    /// Position of the leaf in the tree
+    #[serde(with = "serializers::from_str")]
+    pub index: u64,
+    /// Hash of the leaf
+    #[serde(default, with = "serializers::bytes::base64string")]
+    pub leaf_hash: Vec<u8>,
+    /// Hashes of the siblings of the nodes on the path from the leaf to the
+    /// root, starting from the leaf
+    #[serde(default, with = "serializers::bytes::vec_base64string")]
+    pub aunts: Vec<Vec<u8>>,
+}
+
 #[cfg(test)]
 mod test {
     use super::Proof;
@@ -35,6 +35,7 @@
 //! std::time::Duration  <-> nanoseconds as string #[serde(with="serializers::time_duration")]
 //! Vec<u8>              <-> HexString:            #[serde(with="serializers::bytes::hexstring")]
 //! Vec<u8>              <-> Base64String:         #[serde(with="serializers::bytes::base64string")]
+//! Vec<Vec<u8>>         <-> [Base64String]:       #[serde(with="serializers::bytes::vec_base64string")]
 //! Vec<u8>              <-> String:               #[serde(with="serializers::bytes::string")]
 //!
 //! Notes:
@@ -55,5 +56,8 @@ mod tests;
 
 mod custom;
 pub use custom::null_as_default;
+pub use custom::parse_attribute_string;
 pub use custom::parse_non_empty_block_id;
 pub use custom::parse_non_empty_hash;
+pub use custom::with_attribute_encoding;
+pub use custom::AttributeEncoding;
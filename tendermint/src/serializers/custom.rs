@@ -2,7 +2,9 @@
 
 use crate::{block, Hash};
 use serde::{de::Error as _, Deserialize, Deserializer};
+use std::cell::Cell;
 use std::str::FromStr;
+use subtle_encoding::base64;
 
 // Todo: Refactor the "Option"-based serializers below.
 //  Most of them are not needed if the structs are defined well (with enums).
@@ -69,3 +71,85 @@ where
 {
     Ok(<Option<T>>::deserialize(deserializer)?.unwrap_or_default())
 }
+
+/// Encoding of ABCI event attribute keys and values, which differs between
+/// Tendermint versions
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AttributeEncoding {
+    /// Plain strings, as sent by Tendermint v0.34
+    Plain,
+
+    /// Base64-encoded strings, as sent by Tendermint v0.33
+    Base64,
+
+    /// Guess the encoding of each value: decode it as base64 if it
+    /// round-trips exactly and decodes to valid UTF-8, take it as-is
+    /// otherwise.
+    ///
+    /// Only meant for talking to nodes of both versions at once, as plain
+    /// strings which happen to be valid base64 of UTF-8 text are decoded
+    /// anyway: `"Zm9v"` becomes `"foo"`.
+    Guess,
+}
+
+impl Default for AttributeEncoding {
+    fn default() -> Self {
+        AttributeEncoding::Plain
+    }
+}
+
+thread_local! {
+    static ATTRIBUTE_ENCODING: Cell<AttributeEncoding> = Cell::new(AttributeEncoding::default());
+}
+
+/// Run `f`, deserializing event attributes on the current thread with the
+/// given encoding instead of `AttributeEncoding::Plain`
+pub fn with_attribute_encoding<R>(encoding: AttributeEncoding, f: impl FnOnce() -> R) -> R {
+    /// Restores the previous encoding, even if `f` panics
+    struct Restore(AttributeEncoding);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            ATTRIBUTE_ENCODING.with(|current| current.set(self.0));
+        }
+    }
+
+    let _restore = Restore(ATTRIBUTE_ENCODING.with(|current| current.replace(encoding)));
+    f()
+}
+
+/// Deserialize an event attribute key or value according to the encoding
+/// set by `with_attribute_encoding`.
+///
+/// Tendermint v0.33 encodes empty attributes as `null`, so `null` is read as
+/// an empty string unless the encoding is `AttributeEncoding::Plain`.
+pub fn parse_attribute_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match ATTRIBUTE_ENCODING.with(Cell::get) {
+        AttributeEncoding::Plain => String::deserialize(deserializer),
+        AttributeEncoding::Base64 => {
+            let s = <Option<String>>::deserialize(deserializer)?.unwrap_or_default();
+            let decoded = base64::decode(&s).map_err(|err| {
+                D::Error::custom(format!("invalid base64 attribute {:?}: {}", s, err))
+            })?;
+            String::from_utf8(decoded).map_err(|err| {
+                D::Error::custom(format!("invalid UTF-8 in attribute {:?}: {}", s, err))
+            })
+        }
+        AttributeEncoding::Guess => {
+            let s = <Option<String>>::deserialize(deserializer)?.unwrap_or_default();
+            Ok(decode_base64_string(&s).unwrap_or(s))
+        }
+    }
+}
+
+/// Attempt to strictly decode a base64-encoded UTF-8 string.
+fn decode_base64_string(s: &str) -> Option<String> {
+    let decoded = base64::decode(s).ok()?;
+    if base64::encode(&decoded) != s.as_bytes() {
+        return None;
+    }
+    String::from_utf8(decoded).ok()
+}
@@ -1,5 +1,6 @@
 //! Serialization tests
 
+use super::AttributeEncoding;
 use crate::account::Id;
 use crate::block::CommitSig;
 use crate::test::test_serialization_roundtrip;
@@ -173,3 +174,80 @@ fn deserialize_commit_sig_nil_vote() {
         panic!(format!("expected BlockIDFlagNil, received {:?}", result));
     }
 }
+
+#[test]
+fn deserialize_attribute_string() {
+    #[derive(Deserialize)]
+    struct Attribute {
+        #[serde(deserialize_with = "super::parse_attribute_string")]
+        key: String,
+        #[serde(deserialize_with = "super::parse_attribute_string")]
+        value: String,
+    }
+
+    let parse = |encoding: AttributeEncoding, json: &str| {
+        super::with_attribute_encoding(encoding, || serde_json::from_str::<Attribute>(json))
+    };
+
+    // Tendermint v0.33 encoding
+    let v0_33 = parse(
+        AttributeEncoding::Base64,
+        r#"{"key": "YWN0aW9u", "value": "ZGVsZWdhdGU="}"#,
+    )
+    .unwrap();
+    // Tendermint v0.34 encoding
+    let v0_34 = parse(
+        AttributeEncoding::Plain,
+        r#"{"key": "action", "value": "delegate"}"#,
+    )
+    .unwrap();
+
+    assert_eq!(v0_33.key, "action");
+    assert_eq!(v0_33.value, "delegate");
+    assert_eq!(v0_33.key, v0_34.key);
+    assert_eq!(v0_33.value, v0_34.value);
+
+    // Plain strings which are also valid base64 are kept as-is
+    let plain = parse(
+        AttributeEncoding::Plain,
+        r#"{"key": "abcd", "value": "Zm9v"}"#,
+    )
+    .unwrap();
+    assert_eq!(plain.key, "abcd");
+    assert_eq!(plain.value, "Zm9v");
+
+    // Plain is the default, and doesn't accept null
+    let default: Attribute = serde_json::from_str(r#"{"key": "Zm9v", "value": ""}"#).unwrap();
+    assert_eq!(default.key, "Zm9v");
+    assert!(serde_json::from_str::<Attribute>(r#"{"key": "Zm9v", "value": null}"#).is_err());
+
+    // Tendermint v0.33 sends empty attributes as null, and rejects plain
+    // strings
+    let empty = parse(
+        AttributeEncoding::Base64,
+        r#"{"key": "Zm9v", "value": null}"#,
+    )
+    .unwrap();
+    assert_eq!(empty.value, "");
+    assert!(parse(
+        AttributeEncoding::Base64,
+        r#"{"key": "action", "value": ""}"#
+    )
+    .is_err());
+
+    // Guessing decodes base64 when it round-trips, which corrupts plain
+    // strings that happen to be base64
+    let guessed = parse(
+        AttributeEncoding::Guess,
+        r#"{"key": "test", "value": "ZGVsZWdhdGU="}"#,
+    )
+    .unwrap();
+    assert_eq!(guessed.key, "test");
+    assert_eq!(guessed.value, "delegate");
+    let ambiguous = parse(
+        AttributeEncoding::Guess,
+        r#"{"key": "abcd", "value": "Zm9v"}"#,
+    )
+    .unwrap();
+    assert_eq!(ambiguous.value, "foo");
+}
@@ -168,6 +168,9 @@ mod rpc {
             event_listener::TMEventData::EventDataNewBlock(nb) => {
                 dbg!("got EventDataNewBlock: {:?}", nb);
             }
+            event_listener::TMEventData::EventDataNewBlockHeader(nbh) => {
+                dbg!("got EventDataNewBlockHeader: {:?}", nbh);
+            }
             event_listener::TMEventData::EventDataTx(tx) => {
                 dbg!("got EventDataTx: {:?}", tx);
             }